@@ -0,0 +1,46 @@
+use george_ai::George;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: george-ai cleanup-orphans [--older-than <seconds>]
+
+Commands:
+  cleanup-orphans    Removes Docker containers, networks and images George created which
+                     are older than the threshold (default 3600 seconds). Named sessions and
+                     images in use are kept.";
+
+/// How old a resource must be to be swept when `--older-than` isn't given.
+const DEFAULT_OLDER_THAN: Duration = Duration::from_secs(60 * 60);
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["cleanup-orphans"] => cleanup_orphans(DEFAULT_OLDER_THAN).await,
+        ["cleanup-orphans", "--older-than", seconds] => match seconds.parse() {
+            Ok(seconds) => cleanup_orphans(Duration::from_secs(seconds)).await,
+            Err(_) => {
+                eprintln!("Invalid number of seconds '{}'\n\n{}", seconds, USAGE);
+                ExitCode::FAILURE
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn cleanup_orphans(older_than: Duration) -> ExitCode {
+    match George::cleanup_orphans(older_than).await {
+        Ok(removed) => {
+            println!("Removed {} orphaned container(s), network(s) and image(s)", removed.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to clean up orphans: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use crate::daemon::DaemonSettings;
+use crate::image_options::{ImageEncoding, ImageOptions};
+use crate::retry_policy::RetryPolicy;
+use crate::virtual_machine::{ImagePullPolicy, VirtualMachineSettings};
+use crate::vision::VisionBackend;
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Semaphore;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse config file: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid value '{value}' for {name}")]
+    InvalidValue { name: String, value: String },
+}
+
+/// Everything needed to create a [`crate::George`]: the vision model, retries, the virtual
+/// display and the container it runs in.
+///
+/// A config can be built in code, loaded from a TOML file, read from `GEORGE_*` environment
+/// variables, or any combination of these with [`GeorgeConfig::load`].
+///
+/// # Example
+///
+/// ```rust
+/// use george_ai::{GeorgeConfig, RetryPolicy, VirtualMachineSettings};
+/// use std::time::Duration;
+///
+/// let config = GeorgeConfig::new("https://your-molmo-llm.com")
+///     .set_vision_llm_auth_token("secret")
+///     .set_retry_policy(RetryPolicy::new().set_timeout(Duration::from_secs(30)))
+///     .set_virtual_machine_settings(
+///         VirtualMachineSettings::new()
+///             .set_display_size(1920, 1080)
+///             .add_env(String::from("LANG"), String::from("en_US.UTF-8"))
+///             .add_mount("/tmp/downloads", "/root/Downloads", false),
+///     )
+///     .set_logging(false);
+/// ```
+///
+/// # File format
+///
+/// ```toml
+/// logging = false
+///
+/// [vision]
+/// url = "https://your-molmo-llm.com"
+/// auth_token = "secret"
+/// model = "allenai/Molmo-7B-D-0924"
+/// request_timeout_secs = 60
+/// max_concurrent_requests = 4
+///
+/// [vision.headers]
+/// X-Team = "qa"
+///
+/// [vision.image]
+/// max_dimension = 1024
+/// format = "jpeg"
+/// # Only with format = "jpeg"; png and webp are lossless.
+/// quality = 80
+/// grayscale = true
+///
+/// [retry]
+/// timeout_secs = 30
+/// max_attempts = 20
+///
+/// [display]
+/// width = 1920
+/// height = 1080
+/// depth = 24
+///
+/// [container]
+/// session = "checkout-flow"
+/// image = "registry.example.com/george-daemon:latest"
+/// pull_policy = "always"
+/// env = { LANG = "en_US.UTF-8" }
+/// mounts = [{ host = "/tmp/downloads", container = "/root/Downloads" }]
+/// packages = ["libreoffice"]
+/// dockerfile = "docker/george.Dockerfile"
+/// ```
+#[derive(Clone)]
+pub struct GeorgeConfig {
+    pub(crate) daemon_settings: DaemonSettings,
+    pub(crate) virtual_machine_settings: VirtualMachineSettings,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) vision_backend: Option<Arc<dyn VisionBackend>>,
+    pub(crate) vision_request_permits: Option<Arc<Semaphore>>,
+    pub(crate) logging: Option<bool>,
+}
+
+impl Default for GeorgeConfig {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl GeorgeConfig {
+    /// Creates a config with default settings which uses the Molmo vision LLM at the given URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `vision_llm_url` - The URL of the Molmo vision LLM service.
+    pub fn new(vision_llm_url: &str) -> Self {
+        Self {
+            daemon_settings: DaemonSettings::new(vision_llm_url),
+            virtual_machine_settings: VirtualMachineSettings::new(),
+            retry_policy: RetryPolicy::new(),
+            vision_backend: None,
+            vision_request_permits: None,
+            logging: None,
+        }
+    }
+
+    /// Loads a config from a TOML file. Settings missing from the file keep their defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the TOML file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)?;
+
+        Self::default().with_file(toml::from_str(&contents)?)
+    }
+
+    /// Loads a config from `GEORGE_*` environment variables. Variables which aren't set keep
+    /// their defaults.
+    ///
+    /// The supported variables are `GEORGE_VISION_LLM_URL`, `GEORGE_VISION_LLM_AUTH_TOKEN`,
+    /// `GEORGE_MODEL`, `GEORGE_REQUEST_TIMEOUT_SECS`, `GEORGE_MAX_CONCURRENT_VISION_REQUESTS`,
+    /// `GEORGE_RETRY_TIMEOUT_SECS`, `GEORGE_RETRY_MAX_ATTEMPTS`, `GEORGE_DISPLAY_WIDTH`,
+    /// `GEORGE_DISPLAY_HEIGHT`, `GEORGE_DISPLAY_DEPTH`, `GEORGE_SESSION`, `GEORGE_IMAGE`,
+    /// `GEORGE_IMAGE_PULL_POLICY` and `GEORGE_LOGGING`.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::default().with_env(std::env::vars())
+    }
+
+    /// Loads a config from a TOML file, if given, and then overrides it with any `GEORGE_*`
+    /// environment variables which are set. See [`GeorgeConfig::from_env`] for the variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to an optional TOML file.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let config = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+
+        config.with_env(std::env::vars())
+    }
+
+    pub fn set_vision_llm_url(mut self, vision_llm_url: &str) -> Self {
+        self.daemon_settings = self.daemon_settings.set_vision_llm_url(vision_llm_url.to_string());
+        self
+    }
+
+    pub fn set_vision_llm_auth_token(mut self, token: &str) -> Self {
+        self.daemon_settings = self.daemon_settings.set_vision_llm_auth_token(token.to_string());
+        self
+    }
+
+    /// Replaces the daemon settings, such as the model, prompts and request timeout.
+    pub fn set_daemon_settings(mut self, daemon_settings: DaemonSettings) -> Self {
+        self.daemon_settings = daemon_settings;
+        self
+    }
+
+    /// Replaces the settings for the Docker container, such as the display, image, environment
+    /// variables and mounts.
+    pub fn set_virtual_machine_settings(mut self, virtual_machine_settings: VirtualMachineSettings) -> Self {
+        self.virtual_machine_settings = virtual_machine_settings;
+        self
+    }
+
+    pub fn set_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Interprets the screen with a custom vision backend instead of Molmo.
+    pub fn set_vision_backend(mut self, vision_backend: impl VisionBackend + 'static) -> Self {
+        self.vision_backend = Some(Arc::new(vision_backend));
+        self
+    }
+
+    /// Caps how many vision requests are in flight at once across every George created from this
+    /// config or its clones, such as the sessions of a [`crate::GeorgePool`]. At least one request
+    /// is always allowed.
+    pub fn set_max_concurrent_vision_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.vision_request_permits = Some(Arc::new(Semaphore::new(max_concurrent_requests.max(1))));
+        self
+    }
+
+    /// Turns George's progress output on or off. It is on by default, and configs which don't set
+    /// it leave it as it is.
+    ///
+    /// Logging is process wide, so this applies to every instance once one is created from this
+    /// config.
+    pub fn set_logging(mut self, logging: bool) -> Self {
+        self.logging = Some(logging);
+        self
+    }
+
+    fn with_file(mut self, file: ConfigFile) -> Result<Self, ConfigError> {
+        let vision = file.vision;
+        let mut daemon_settings = self.daemon_settings;
+        if let Some(url) = vision.url {
+            daemon_settings = daemon_settings.set_vision_llm_url(url);
+        }
+        if let Some(auth_token) = vision.auth_token {
+            daemon_settings = daemon_settings.set_vision_llm_auth_token(auth_token);
+        }
+        if let Some(model) = vision.model {
+            daemon_settings = daemon_settings.set_model(model);
+        }
+        if let Some(temperature) = vision.temperature {
+            daemon_settings = daemon_settings.set_temperature(temperature);
+        }
+        if vision.top_k.is_some() {
+            daemon_settings = daemon_settings.set_top_k(vision.top_k);
+        }
+        if vision.top_p.is_some() {
+            daemon_settings = daemon_settings.set_top_p(vision.top_p);
+        }
+        if vision.max_tokens.is_some() {
+            daemon_settings = daemon_settings.set_max_tokens(vision.max_tokens);
+        }
+        if let Some(seconds) = vision.request_timeout_secs {
+            daemon_settings = daemon_settings.set_request_timeout(Duration::from_secs(seconds));
+        }
+        for (name, value) in vision.headers {
+            daemon_settings = daemon_settings.add_header(name, value);
+        }
+        if let Some(image) = vision.image {
+            daemon_settings = daemon_settings.set_image_options(image.into_image_options()?);
+        }
+        self.daemon_settings = daemon_settings;
+        if let Some(max_concurrent_requests) = vision.max_concurrent_requests {
+            let max_concurrent_requests = non_zero("vision.max_concurrent_requests", max_concurrent_requests)?;
+            self = self.set_max_concurrent_vision_requests(max_concurrent_requests);
+        }
+
+        if let Some(seconds) = file.retry.timeout_secs {
+            self.retry_policy = self.retry_policy.set_timeout(Duration::from_secs(seconds));
+        }
+        if let Some(max_attempts) = file.retry.max_attempts {
+            self.retry_policy = self.retry_policy.set_max_attempts(max_attempts);
+        }
+
+        let mut virtual_machine_settings = self.virtual_machine_settings;
+        let width = file.display.width.unwrap_or(virtual_machine_settings.display_width);
+        let height = file.display.height.unwrap_or(virtual_machine_settings.display_height);
+        virtual_machine_settings = virtual_machine_settings.set_display_size(width, height);
+        if let Some(depth) = file.display.depth {
+            virtual_machine_settings = virtual_machine_settings.set_display_depth(depth);
+        }
+        if let Some(session) = file.container.session {
+            virtual_machine_settings = virtual_machine_settings.set_session_name(session);
+        }
+        if let Some(image) = file.container.image {
+            virtual_machine_settings = virtual_machine_settings.set_image(image);
+        }
+        if let Some(pull_policy) = file.container.pull_policy {
+            virtual_machine_settings = virtual_machine_settings.set_image_pull_policy(pull_policy);
+        }
+        for (name, value) in file.container.env {
+            virtual_machine_settings = virtual_machine_settings.add_env(name, value);
+        }
+        for mount in file.container.mounts {
+            virtual_machine_settings = virtual_machine_settings.add_mount(&mount.host, &mount.container, mount.read_only);
+        }
+        for package in file.container.packages {
+            virtual_machine_settings = virtual_machine_settings.add_package(package);
+        }
+        if let Some(dockerfile) = file.container.dockerfile {
+            virtual_machine_settings = virtual_machine_settings.set_dockerfile(dockerfile);
+        }
+        self.virtual_machine_settings = virtual_machine_settings;
+
+        if file.logging.is_some() {
+            self.logging = file.logging;
+        }
+
+        Ok(self)
+    }
+
+    fn with_env(mut self, vars: impl Iterator<Item = (String, String)>) -> Result<Self, ConfigError> {
+        for (name, value) in vars {
+            match name.as_str() {
+                "GEORGE_VISION_LLM_URL" => self = self.set_vision_llm_url(&value),
+                "GEORGE_VISION_LLM_AUTH_TOKEN" => self = self.set_vision_llm_auth_token(&value),
+                "GEORGE_MODEL" => self.daemon_settings = self.daemon_settings.set_model(value),
+                "GEORGE_REQUEST_TIMEOUT_SECS" => {
+                    let seconds = parse_var(&name, &value)?;
+                    self.daemon_settings = self.daemon_settings.set_request_timeout(Duration::from_secs(seconds));
+                }
+                "GEORGE_MAX_CONCURRENT_VISION_REQUESTS" => {
+                    let max_concurrent_requests = non_zero(&name, parse_var(&name, &value)?)?;
+                    self = self.set_max_concurrent_vision_requests(max_concurrent_requests);
+                }
+                "GEORGE_RETRY_TIMEOUT_SECS" => {
+                    let seconds = parse_var(&name, &value)?;
+                    self.retry_policy = self.retry_policy.set_timeout(Duration::from_secs(seconds));
+                }
+                "GEORGE_RETRY_MAX_ATTEMPTS" => {
+                    self.retry_policy = self.retry_policy.set_max_attempts(parse_var(&name, &value)?);
+                }
+                "GEORGE_DISPLAY_WIDTH" => self.virtual_machine_settings.display_width = parse_var(&name, &value)?,
+                "GEORGE_DISPLAY_HEIGHT" => self.virtual_machine_settings.display_height = parse_var(&name, &value)?,
+                "GEORGE_DISPLAY_DEPTH" => self.virtual_machine_settings.display_depth = parse_var(&name, &value)?,
+                "GEORGE_SESSION" => self.virtual_machine_settings = self.virtual_machine_settings.set_session_name(value),
+                "GEORGE_IMAGE" => self.virtual_machine_settings = self.virtual_machine_settings.set_image(value),
+                "GEORGE_IMAGE_PULL_POLICY" => {
+                    let deserializer: serde::de::value::StrDeserializer<serde::de::value::Error> = value.as_str().into_deserializer();
+                    let pull_policy = ImagePullPolicy::deserialize(deserializer).map_err(|_| ConfigError::InvalidValue {
+                        name: name.clone(),
+                        value: value.clone(),
+                    })?;
+                    self.virtual_machine_settings = self.virtual_machine_settings.set_image_pull_policy(pull_policy);
+                }
+                "GEORGE_LOGGING" => self.logging = Some(parse_var(&name, &value)?),
+                _ => {}
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+fn parse_var<T: FromStr>(name: &str, value: &str) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|_| ConfigError::InvalidValue {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn non_zero(name: &str, value: usize) -> Result<usize, ConfigError> {
+    if value == 0 {
+        return Err(ConfigError::InvalidValue { name: name.to_string(), value: value.to_string() });
+    }
+
+    Ok(value)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    logging: Option<bool>,
+    vision: VisionSection,
+    retry: RetrySection,
+    display: DisplaySection,
+    container: ContainerSection,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct VisionSection {
+    url: Option<String>,
+    auth_token: Option<String>,
+    model: Option<String>,
+    temperature: Option<f32>,
+    top_k: Option<u32>,
+    top_p: Option<f32>,
+    max_tokens: Option<u32>,
+    request_timeout_secs: Option<u64>,
+    max_concurrent_requests: Option<usize>,
+    headers: BTreeMap<String, String>,
+    image: Option<ImageSection>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ImageSection {
+    max_dimension: Option<u32>,
+    format: Option<String>,
+    quality: Option<u8>,
+    grayscale: bool,
+}
+
+impl ImageSection {
+    fn into_image_options(self) -> Result<ImageOptions, ConfigError> {
+        let mut image_options = ImageOptions::new().set_grayscale(self.grayscale);
+        if let Some(max_dimension) = self.max_dimension {
+            image_options = image_options.set_max_dimension(max_dimension);
+        }
+
+        let is_jpeg = matches!(self.format.as_deref(), Some("jpeg") | Some("jpg"));
+        if let (Some(quality), false) = (self.quality, is_jpeg) {
+            // Only JPEG is lossy, so a quality would be silently ignored for any other format.
+            return Err(ConfigError::InvalidValue {
+                name: String::from("vision.image.quality (only supported with format = \"jpeg\")"),
+                value: quality.to_string(),
+            });
+        }
+
+        let encoding = match self.format.as_deref() {
+            None => None,
+            Some("png") => Some(ImageEncoding::Png),
+            Some("jpeg") | Some("jpg") => Some(ImageEncoding::Jpeg { quality: self.quality.unwrap_or(90) }),
+            Some("webp") => Some(ImageEncoding::WebP),
+            Some(format) => return Err(ConfigError::InvalidValue {
+                name: String::from("vision.image.format"),
+                value: format.to_string(),
+            }),
+        };
+        if let Some(encoding) = encoding {
+            image_options = image_options.set_encoding(encoding);
+        }
+
+        Ok(image_options)
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RetrySection {
+    timeout_secs: Option<u64>,
+    max_attempts: Option<u32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct DisplaySection {
+    width: Option<u32>,
+    height: Option<u32>,
+    depth: Option<u8>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ContainerSection {
+    session: Option<String>,
+    image: Option<String>,
+    pull_policy: Option<ImagePullPolicy>,
+    env: BTreeMap<String, String>,
+    mounts: Vec<MountSection>,
+    packages: Vec<String>,
+    dockerfile: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MountSection {
+    host: String,
+    container: String,
+    #[serde(default)]
+    read_only: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<GeorgeConfig, ConfigError> {
+        GeorgeConfig::default().with_file(toml::from_str(contents)?)
+    }
+
+    #[test]
+    fn test_parse_config_file() {
+        let config = parse(r#"
+            logging = false
+
+            [vision]
+            url = "https://molmo.example.com"
+            auth_token = "secret"
+            request_timeout_secs = 60
+            max_concurrent_requests = 4
+            headers = { X-Team = "qa" }
+            image = { max_dimension = 1024, format = "jpeg", quality = 75 }
+
+            [retry]
+            max_attempts = 3
+
+            [display]
+            width = 1920
+            height = 1080
+
+            [container]
+            session = "checkout-flow"
+            image = "george-daemon:latest"
+            pull_policy = "never"
+            env = { LANG = "en_US.UTF-8" }
+            mounts = [{ host = "/tmp/downloads", container = "/root/Downloads", read_only = true }]
+            packages = ["libreoffice"]
+        "#).unwrap();
+
+        assert_eq!(config.logging, Some(false));
+        assert_eq!(config.daemon_settings.vision_llm_url, "https://molmo.example.com");
+        assert_eq!(config.daemon_settings.vision_llm_auth_token, "secret");
+        assert_eq!(config.daemon_settings.request_timeout, Some(Duration::from_secs(60)));
+        assert_eq!(config.vision_request_permits.map(|permits| permits.available_permits()), Some(4));
+        assert_eq!(config.daemon_settings.headers, vec![(String::from("X-Team"), String::from("qa"))]);
+        assert_eq!(config.daemon_settings.image_options, ImageOptions::new()
+            .set_max_dimension(1024)
+            .set_encoding(ImageEncoding::Jpeg { quality: 75 }));
+
+        let virtual_machine_settings = config.virtual_machine_settings;
+        assert_eq!((virtual_machine_settings.display_width, virtual_machine_settings.display_height), (1920, 1080));
+        assert_eq!(virtual_machine_settings.display_depth, 16);
+        assert_eq!(virtual_machine_settings.session_name.as_deref(), Some("checkout-flow"));
+        assert_eq!(virtual_machine_settings.image.as_deref(), Some("george-daemon:latest"));
+        assert_eq!(virtual_machine_settings.image_pull_policy, ImagePullPolicy::Never);
+        assert_eq!(virtual_machine_settings.env, vec![(String::from("LANG"), String::from("en_US.UTF-8"))]);
+        assert_eq!(virtual_machine_settings.mounts, vec![String::from("/tmp/downloads:/root/Downloads:ro")]);
+        assert_eq!(virtual_machine_settings.packages, vec![String::from("libreoffice")]);
+    }
+
+    #[test]
+    fn test_parse_config_file_with_unknown_field() {
+        assert!(matches!(parse("[display]\nwidht = 1920"), Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn test_parse_config_file_with_unknown_image_format() {
+        assert!(matches!(parse("[vision.image]\nformat = \"gif\""), Err(ConfigError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_parse_config_file_with_quality_for_a_lossless_format() {
+        assert!(matches!(parse("[vision.image]\nformat = \"webp\"\nquality = 80"), Err(ConfigError::InvalidValue { .. })));
+        assert!(matches!(parse("[vision.image]\nquality = 80"), Err(ConfigError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let config = parse("[vision]\nurl = \"https://from-file.example.com\"\n[display]\nwidth = 1920")
+            .unwrap()
+            .with_env(vec![
+                (String::from("GEORGE_VISION_LLM_URL"), String::from("https://from-env.example.com")),
+                (String::from("GEORGE_DISPLAY_HEIGHT"), String::from("1200")),
+                (String::from("GEORGE_LOGGING"), String::from("false")),
+                (String::from("GEORGE_IMAGE_PULL_POLICY"), String::from("always")),
+                (String::from("PATH"), String::from("/usr/bin")),
+            ].into_iter())
+            .unwrap();
+
+        assert_eq!(config.daemon_settings.vision_llm_url, "https://from-env.example.com");
+        assert_eq!(config.virtual_machine_settings.display_width, 1920);
+        assert_eq!(config.virtual_machine_settings.display_height, 1200);
+        assert_eq!(config.virtual_machine_settings.image_pull_policy, ImagePullPolicy::Always);
+        assert_eq!(config.logging, Some(false));
+    }
+
+    #[test]
+    fn test_logging_is_left_alone_unless_set() {
+        let config = parse("[display]\nwidth = 1920").unwrap();
+
+        assert_eq!(config.logging, None);
+    }
+
+    #[test]
+    fn test_env_with_invalid_value() {
+        let result = GeorgeConfig::default()
+            .with_env(std::iter::once((String::from("GEORGE_DISPLAY_WIDTH"), String::from("wide"))));
+
+        assert!(matches!(result, Err(ConfigError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_zero_concurrent_vision_requests_is_invalid() {
+        let result = GeorgeConfig::default()
+            .with_env(std::iter::once((String::from("GEORGE_MAX_CONCURRENT_VISION_REQUESTS"), String::from("0"))));
+
+        assert!(matches!(result, Err(ConfigError::InvalidValue { .. })));
+        assert!(matches!(parse("[vision]\nmax_concurrent_requests = 0"), Err(ConfigError::InvalidValue { .. })));
+    }
+}
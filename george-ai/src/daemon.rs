@@ -1,15 +1,26 @@
-use base64::{engine::general_purpose, Engine as _};
+use crate::image_options::ImageOptions;
+use crate::input::{ClickOptions, DragOptions, KeyDirection, MouseButton, ScrollAxis};
+use crate::logging::log;
+use crate::scope::{ElementBox, Region};
+use crate::segmentation::segment;
+use crate::verification::{vote, Verification, Vote};
+use crate::vision::{LimitedVisionBackend, MolmoBackend, VisionBackend};
 use bytes::Bytes;
+use futures_util::future::join_all;
 use image::ImageFormat;
 use image::ImageReader;
 use reqwest::{Client, Response};
-use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
 use std::time::Duration;
-use regex::Regex;
 use thiserror::{Error};
+use tokio::sync::Semaphore;
 use tokio::time::{sleep, timeout};
 
+/// How far apart in pixels the points of elements in one row may be vertically.
+const ROW_TOLERANCE: u32 = 10;
+
 #[derive(Error, Debug)]
 pub enum DaemonError {
     #[error("Daemon not started")]
@@ -26,49 +37,96 @@ pub enum DaemonError {
     FailedToParseExistence(String),
     #[error("Screenshot failed: {0}")]
     ScreenshotFailed(String),
+    #[error("Vision request failed with status {status}: {body}")]
+    VisionRequestFailed { status: u16, body: String },
     #[error("Unexpected error: {0}")]
     Unexpected(String),
     #[error("Timeout while trying to find selector: {0}")]
     SelectorTimeout(String),
+    #[error("Not visible after scrolling: {0}")]
+    NotVisibleAfterScrolling(String),
+    #[error("Text is not visible: {0}")]
+    TextNotVisible(String),
+    #[error("Selected option is not visible: {0}")]
+    SelectionNotVerified(String),
+    #[error("Region is outside of the screen: {0}")]
+    RegionOutOfBounds(String),
+    #[error("Only {confidence:.2} of the samples agree on the location of: {selector}")]
+    LowConfidence { selector: String, confidence: f64 },
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-struct FindResponse {
-    choices: Vec<Choice>,
+/// How [`Daemon::element_box`] finds the box around an element.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BoxMode {
+    /// Asks the vision model for the element's top left and bottom right corners.
+    #[default]
+    Corners,
+    /// Asks the vision model for the element's center and grows the box from there by color,
+    /// which takes one request instead of two.
+    Segmentation,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-struct Choice {
-    message: Message,
+#[derive(Deserialize)]
+struct MousePosition {
+    x: i32,
+    y: i32,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-struct Message {
-    content: String,
+#[derive(Deserialize)]
+struct Display {
+    width: u32,
+    height: u32,
 }
 
-
 pub struct Daemon {
-    port: Option<String>,
+    base_url: Option<String>,
     client: Client,
     pub settings: DaemonSettings,
+    vision_backend: Option<Arc<dyn VisionBackend>>,
+    vision_request_permits: Option<Arc<Semaphore>>,
 }
 
 #[derive(Clone, Debug)]
 pub struct DaemonSettings {
     vision_coordinate_prompt: String,
-    vision_llm_url: String,
-    vision_llm_auth_token: String,
+    vision_corner_prompt: String,
+    vision_all_coordinates_prompt: String,
+    pub(crate) vision_llm_url: String,
+    pub(crate) vision_llm_auth_token: String,
     is_text_visible_prompt: String,
+    pub(crate) model: String,
+    pub(crate) temperature: f32,
+    pub(crate) top_k: Option<u32>,
+    pub(crate) top_p: Option<f32>,
+    pub(crate) max_tokens: Option<u32>,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) request_timeout: Option<Duration>,
+    pub(crate) request_template: Option<Value>,
+    pub(crate) image_options: ImageOptions,
+    pub(crate) box_mode: BoxMode,
+    pub(crate) verification: Option<Verification>,
 }
 
 impl DaemonSettings {
     pub fn new(vision_llm_url: &str) -> Self {
         Self {
             vision_coordinate_prompt: String::from("You are a helpful assistant that is to be used in finding coordinates of items in an image. You are finding coordinates so you can be part of a automated AI tool. You need to be as accurate as possible. Find the point coordinate of the center of the "),
+            vision_corner_prompt: String::from("You are a helpful assistant that is to be used in finding coordinates of items in an image. You are finding coordinates so you can be part of a automated AI tool. You need to be as accurate as possible. Find the point coordinate of the {corner} corner of the "),
+            vision_all_coordinates_prompt: String::from("You are a helpful assistant that is to be used in finding coordinates of items in an image. You are finding coordinates so you can be part of a automated AI tool. You need to be as accurate as possible. Point to the center of every "),
             is_text_visible_prompt: String::from("find all the text on the screen. return it in an array list"),
             vision_llm_url: vision_llm_url.to_string(),
-            vision_llm_auth_token: String::from("token-not-needed-to-local-llm")
+            vision_llm_auth_token: String::from("token-not-needed-to-local-llm"),
+            model: String::from("allenai/Molmo-7B-D-0924"),
+            temperature: 0.0,
+            top_k: Some(1),
+            top_p: None,
+            max_tokens: None,
+            headers: Vec::new(),
+            request_timeout: None,
+            request_template: None,
+            image_options: ImageOptions::new(),
+            box_mode: BoxMode::default(),
+            verification: None,
         }
     }
 
@@ -77,52 +135,180 @@ impl DaemonSettings {
         self
     }
 
+    /// Sets the prompt used to find the corners of an element. `{corner}` is replaced with
+    /// "top left" or "bottom right" and the selector is appended.
+    pub fn set_vision_corner_prompt(mut self, vision_corner_prompt: String) -> Self {
+        self.vision_corner_prompt = vision_corner_prompt;
+        self
+    }
+
+    /// Sets the prompt used to find every element matching a selector. The selector is appended.
+    pub fn set_vision_all_coordinates_prompt(mut self, vision_all_coordinates_prompt: String) -> Self {
+        self.vision_all_coordinates_prompt = vision_all_coordinates_prompt;
+        self
+    }
+
     pub fn set_is_text_visible_prompt(mut self, is_text_visible_prompt: String) -> Self {
         self.is_text_visible_prompt = is_text_visible_prompt;
         self
     }
 
+    pub fn set_vision_llm_url(mut self, vision_llm_url: String) -> Self {
+        self.vision_llm_url = vision_llm_url;
+        self
+    }
+
     pub fn set_vision_llm_auth_token(mut self, vision_llm_auth_token: String) -> Self {
         self.vision_llm_auth_token = vision_llm_auth_token;
         self
     }
+
+    /// Sets the model id sent to the vision LLM server. Defaults to `allenai/Molmo-7B-D-0924`.
+    pub fn set_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Sets the sampling temperature. Defaults to `0`.
+    pub fn set_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Sets top-k sampling, or omits it from the request when `None`. Defaults to `1`.
+    pub fn set_top_k(mut self, top_k: Option<u32>) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Sets nucleus sampling, or omits it from the request when `None`. Defaults to `None`.
+    pub fn set_top_p(mut self, top_p: Option<f32>) -> Self {
+        self.top_p = top_p;
+        self
+    }
+
+    /// Limits the number of tokens the model may generate. Defaults to `None`.
+    pub fn set_max_tokens(mut self, max_tokens: Option<u32>) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Adds a header sent with every request to the vision LLM server.
+    pub fn add_header(mut self, name: String, value: String) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Sets how long to wait for the vision LLM server to respond. Defaults to no timeout.
+    pub fn set_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    /// Replaces the chat completions request body with a template.
+    ///
+    /// The strings `{{model}}`, `{{prompt}}` and `{{image_url}}` are replaced anywhere they
+    /// appear in the template. `{{temperature}}`, `{{top_k}}`, `{{top_p}}` and `{{max_tokens}}`
+    /// are replaced with numbers (or `null` when unset) when they are the entire string value.
+    pub fn set_request_template(mut self, request_template: Value) -> Self {
+        self.request_template = Some(request_template);
+        self
+    }
+
+    /// Sets how screenshots are resized and encoded before they are sent to the vision model.
+    pub fn set_image_options(mut self, image_options: ImageOptions) -> Self {
+        self.image_options = image_options;
+        self
+    }
+
+    /// Samples the location of every element several times and fails with
+    /// [`DaemonError::LowConfidence`] when they don't agree, instead of trusting a single answer.
+    pub fn set_verification(mut self, verification: Verification) -> Self {
+        self.verification = Some(verification);
+        self
+    }
+
+    /// Sets how the box around an element is found. Defaults to [`BoxMode::Corners`].
+    pub fn set_box_mode(mut self, box_mode: BoxMode) -> Self {
+        self.box_mode = box_mode;
+        self
+    }
 }
 
 
 impl Daemon {
     pub fn new(visual_llm_url: &str) -> Self {
         Self {
-            port: None,
+            base_url: None,
             client: Client::new(),
             settings: DaemonSettings::new(visual_llm_url),
+            vision_backend: None,
+            vision_request_permits: None,
         }
     }
 
     pub fn with_settings(settings: DaemonSettings) -> Self {
         Self {
-            port: None,
+            base_url: None,
             client: Client::new(),
             settings,
+            vision_backend: None,
+            vision_request_permits: None,
+        }
+    }
+
+    /// Replaces the default Molmo backend with a custom vision backend.
+    pub fn set_vision_backend(&mut self, vision_backend: Arc<dyn VisionBackend>) {
+        self.vision_backend = Some(vision_backend);
+    }
+
+    /// Limits how many vision requests are in flight at once across everyone sharing the permits,
+    /// whichever backend is in use.
+    pub(crate) fn set_vision_request_permits(&mut self, permits: Arc<Semaphore>) {
+        self.vision_request_permits = Some(permits);
+    }
+
+    /// Returns the vision backend used to interpret screenshots. When no custom backend has been
+    /// set, a Molmo backend is built from the current settings.
+    pub fn vision_backend(&self) -> Arc<dyn VisionBackend> {
+        let vision_backend = match &self.vision_backend {
+            Some(vision_backend) => vision_backend.clone(),
+            None => Arc::new(MolmoBackend::with_client(self.client.clone(), self.settings.clone())),
+        };
+
+        match &self.vision_request_permits {
+            Some(permits) => Arc::new(LimitedVisionBackend::with_permits(vision_backend, permits.clone())),
+            None => vision_backend,
         }
     }
 
+    /// Targets a daemon listening on the given port of this machine.
     pub fn set_port(&mut self, port: String) {
-        self.port = Some(port);
+        self.set_base_url(&format!("http://127.0.0.1:{}", port));
+    }
+
+    /// Targets a daemon at the given URL, e.g. `http://192.168.1.20:3000`.
+    pub fn set_base_url(&mut self, base_url: &str) {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
     }
 
     fn build_url(&self, endpoint: &str) -> Result<String, DaemonError> {
-        let port = self.port.as_ref().ok_or(DaemonError::NotStarted)?;
-        Ok(format!("http://127.0.0.1:{}/{}", port, endpoint))
+        let base_url = self.base_url.as_ref().ok_or(DaemonError::NotStarted)?;
+        Ok(format!("{}/{}", base_url, endpoint))
     }
 
-    async fn click_coordinate(&self, x: u32, y: u32) -> Result<(), DaemonError> {
-        let url = self.build_url("click")?;
-        let body = json!({
-            "x": x,
-            "y": y,
-        });
-        let res = self.client.post(url)
-            .json(&body)
+    pub(crate) async fn click_coordinate(&self, x: u32, y: u32, options: &ClickOptions) -> Result<(), DaemonError> {
+        let mut body = serde_json::to_value(options)?;
+        body["x"] = json!(x);
+        body["y"] = json!(y);
+
+        self.post_action("click", &body, "send click").await
+    }
+
+    /// Posts an action to the daemon, describing it as `description` if the daemon rejects it.
+    async fn post_action(&self, endpoint: &str, body: &Value, description: &str) -> Result<(), DaemonError> {
+        let res = self.client.post(self.build_url(endpoint)?)
+            .json(body)
             .send()
             .await?;
 
@@ -130,10 +316,10 @@ impl Daemon {
             Ok(())
         } else {
             let status = res.status();
-            let text = res.text().await?;
+            let response_text = res.text().await?;
             Err(DaemonError::Unexpected(format!(
-                "Failed to send click: Status: {}, Body: {}",
-                status, text
+                "Failed to {}: Status: {}, Body: {}",
+                description, status, response_text
             )))
         }
     }
@@ -160,25 +346,94 @@ impl Daemon {
     }
 
     pub async fn type_text(&self, text: &str) -> Result<(), DaemonError> {
-        let res = self.client.post(self.build_url("type")?)
-            .json(&json!({
-                "text": text,
-            }))
-            .send()
-            .await?;
+        self.post_action("type", &json!({ "text": text }), "send text").await
+    }
 
-        if res.status().is_success() {
-            Ok(())
+    /// Sends a chord of keys such as `ctrl+a` or `enter` to the daemon.
+    pub async fn key(&self, keys: &str, direction: KeyDirection) -> Result<(), DaemonError> {
+        let body = json!({
+            "keys": keys,
+            "direction": direction,
+        });
+
+        self.post_action("key", &body, "send keys").await
+    }
+
+    /// Scrolls by `amount` wheel clicks, optionally with the mouse moved to `at` first. Positive
+    /// amounts scroll down or right.
+    pub async fn scroll(&self, amount: i32, axis: ScrollAxis, at: Option<(u32, u32)>) -> Result<(), DaemonError> {
+        let body = json!({
+            "amount": amount,
+            "axis": axis,
+            "x": at.map(|(x, _)| x),
+            "y": at.map(|(_, y)| y),
+        });
+
+        self.post_action("scroll", &body, "scroll").await
+    }
+
+    /// Moves the mouse to an absolute screen coordinate without clicking.
+    pub async fn move_mouse(&self, x: u32, y: u32) -> Result<(), DaemonError> {
+        self.post_action("mouse/move", &json!({ "x": x, "y": y }), "move mouse").await
+    }
+
+    /// Returns the width and height of the screen in pixels.
+    pub async fn display_size(&self) -> Result<(u32, u32), DaemonError> {
+        let response = self.client.get(self.build_url("display")?).send().await?;
+
+        if response.status().is_success() {
+            let display: Display = response.json().await?;
+            Ok((display.width, display.height))
         } else {
-            let status = res.status();
-            let response_text = res.text().await?;
+            let status = response.status();
+            let body = response.text().await?;
             Err(DaemonError::Unexpected(format!(
-                "Failed to send text: Status: {}, Body: {}",
-                status, response_text
+                "Failed to get display size: Status: {}, Body: {}",
+                status, body
+            )))
+        }
+    }
+
+    /// Returns the current position of the mouse in screen pixels.
+    pub async fn mouse_position(&self) -> Result<(i32, i32), DaemonError> {
+        let response = self.client.get(self.build_url("mouse")?).send().await?;
+
+        if response.status().is_success() {
+            let position: MousePosition = response.json().await?;
+            Ok((position.x, position.y))
+        } else {
+            let status = response.status();
+            let body = response.text().await?;
+            Err(DaemonError::Unexpected(format!(
+                "Failed to get mouse position: Status: {}, Body: {}",
+                status, body
             )))
         }
     }
 
+    /// Presses a mouse button wherever the mouse currently is, without releasing it.
+    pub async fn mouse_down(&self, button: MouseButton) -> Result<(), DaemonError> {
+        self.post_action("mouse/down", &json!({ "button": button }), "press mouse button").await
+    }
+
+    /// Releases a mouse button wherever the mouse currently is.
+    pub async fn mouse_up(&self, button: MouseButton) -> Result<(), DaemonError> {
+        self.post_action("mouse/up", &json!({ "button": button }), "release mouse button").await
+    }
+
+    /// Drags from one absolute screen coordinate to another.
+    pub async fn drag(&self, from: (u32, u32), to: (u32, u32), options: &DragOptions) -> Result<(), DaemonError> {
+        let body = json!({
+            "from": { "x": from.0, "y": from.1 },
+            "to": { "x": to.0, "y": to.1 },
+            "button": options.button,
+            "steps": options.steps,
+            "step_delay_ms": options.step_delay.as_millis() as u64,
+        });
+
+        self.post_action("drag", &body, "drag").await
+    }
+
     pub async fn ready(&self) -> Result<(), DaemonError> {
         let timeout_duration = Duration::from_secs(10);
 
@@ -206,170 +461,233 @@ impl Daemon {
     }
 
     pub async fn is_text_visible_from_prompt(&self, prompt: &str) -> Result<Vec<String>, DaemonError> {
-        println!();
-        println!("prompt: {}", prompt);
-        let screenshot_bytes = self.screenshot().await?;
+        self.is_text_visible_from_prompt_within(prompt, None).await
+    }
 
-        let image_base64 = general_purpose::STANDARD.encode(&screenshot_bytes);
-
-        let request_body = json!({
-            "model": "allenai/Molmo-7B-D-0924",
-            "messages": [
-                {
-                    "role": "user",
-                    "content": [
-                        {"type": "text", "text": prompt},
-                        {"type": "image_url", "image_url": {"url": format!("data:image/jpeg;base64,{}", image_base64)}}
-                    ]
-                }
-            ],
-            "temperature": 0,
-            "top_k": 1
-        });
+    async fn is_text_visible_from_prompt_within(&self, prompt: &str, region: Option<Region>) -> Result<Vec<String>, DaemonError> {
+        let (screenshot_bytes, _) = self.screenshot_within(region).await?;
 
-        let response = self.client
-            .post(format!("{}/v1/chat/completions", self.settings.vision_llm_url))
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.settings.vision_llm_auth_token))
-            .json(&request_body)
-            .send()
-            .await?;
+        self.vision_backend().visible_text(&screenshot_bytes, prompt).await
+    }
 
-        if !response.status().is_success() {
-            return Err(DaemonError::Unexpected("Failed to process the existence check request.".into()));
-        }
+    pub async fn is_text_visible(&self, text: &str) -> Result<bool, DaemonError> {
+        self.is_text_visible_within(text, None).await
+    }
+
+    /// Checks whether the text is visible like [`Daemon::is_text_visible`], only looking at the
+    /// region of the screen when one is given.
+    pub async fn is_text_visible_within(&self, text: &str, region: Option<Region>) -> Result<bool, DaemonError> {
+        let visible_texts = self.is_text_visible_from_prompt_within(self.settings.is_text_visible_prompt.as_str(), region).await?;
+        let lowercase_text = text.to_lowercase();
+
+        Ok(visible_texts.iter().any(|t| t.to_lowercase() == lowercase_text))
+    }
 
-        let response_body: FindResponse = response.json().await?;
-        let response_text = serde_json::to_string(&response_body)?;
-        println!("Full response body: {}", response_text);
+    /// Asks the vision backend a free-form question about the current screen.
+    pub async fn ask(&self, prompt: &str) -> Result<String, DaemonError> {
+        self.ask_within(prompt, None).await
+    }
 
-        let content = response_body.choices.first()
-            .ok_or_else(|| DaemonError::Unexpected(format!("No choices in response. Prompt: {}", prompt)))?
-            .message.content.trim().to_lowercase();
+    /// Asks the vision backend a free-form question like [`Daemon::ask`], only showing it the
+    /// region of the screen when one is given.
+    pub async fn ask_within(&self, prompt: &str, region: Option<Region>) -> Result<String, DaemonError> {
+        let (screenshot_bytes, _) = self.screenshot_within(region).await?;
 
+        self.vision_backend().ask(&screenshot_bytes, prompt).await
+    }
 
-        println!("content: {:?}", content);
-        let visible_text = self.parse_visible_text(content.as_str())?;
-        Ok(visible_text)
+    pub async fn coordinate_of_from_prompt(&self, prompt: &str) -> Result<(u32, u32), DaemonError> {
+        self.coordinate_of_from_prompt_within(prompt, None).await
     }
 
-    pub async fn is_text_visible(&self, text: &str) -> Result<bool, DaemonError> {
-        let visible_texts = self.is_text_visible_from_prompt(self.settings.is_text_visible_prompt.as_str()).await?;
-        let lowercase_text = text.to_lowercase();
+    /// Locates the element described by the prompt like [`Daemon::coordinate_of_from_prompt`].
+    /// When a region is given, only that part of the screen is sent to the vision backend and the
+    /// coordinate is translated back to screen pixels.
+    pub async fn coordinate_of_from_prompt_within(&self, prompt: &str, region: Option<Region>) -> Result<(u32, u32), DaemonError> {
+        self.locate_within(&describe_prompt(prompt), &[prompt.to_string()], region).await
+    }
 
-        Ok(visible_texts.iter().any(|t| t.to_lowercase() == lowercase_text))
+    pub async fn coordinate_of(&self, selector: &str) -> Result<(u32, u32), DaemonError> {
+        self.coordinate_of_within(selector, None).await
     }
 
+    /// Locates the element identified by the selector, only looking at the region of the screen
+    /// when one is given.
+    pub async fn coordinate_of_within(&self, selector: &str, region: Option<Region>) -> Result<(u32, u32), DaemonError> {
+        let prompts = self.coordinate_prompts(self.settings.verification.as_ref(), selector);
+        self.locate_within(selector, &prompts, region).await
+    }
+
+    /// Samples the location of the element identified by the selector as set with
+    /// [`DaemonSettings::set_verification`], or with the default [`Verification`] if none is
+    /// set, and reports how much the samples agree without failing on low confidence.
+    pub async fn vote_on_coordinate_of(&self, selector: &str) -> Result<Vote, DaemonError> {
+        let verification = self.settings.verification.clone().unwrap_or_default();
+        let prompts = self.coordinate_prompts(Some(&verification), selector);
 
-    pub(crate) fn parse_visible_text(&self, content: &str) -> Result<Vec<String>, DaemonError> {
-        let re = Regex::new(r#""(.*?)""#).unwrap(); // Matches text within double quotes
-        let mut visible_text = Vec::new();
+        let screenshot = self.screenshot_within(None).await?;
 
+        self.vote_in(&screenshot, &verification, &prompts).await
+    }
 
-        for cap in re.captures_iter(content) {
-            if let Some(text) = cap.get(1) {
-                visible_text.push(text.as_str().trim_end_matches('\\').to_string());
+    fn coordinate_prompts(&self, verification: Option<&Verification>, selector: &str) -> Vec<String> {
+        let prompt_variants = verification.into_iter().flat_map(|verification| verification.prompt_variants.iter());
+
+        std::iter::once(&self.settings.vision_coordinate_prompt)
+            .chain(prompt_variants)
+            .map(|prompt| format!("{} {}", prompt, selector))
+            .collect()
+    }
+
+    /// Locates an element with the first prompt or, when verification is set, by voting across
+    /// all of them. `description` names the element in [`DaemonError::LowConfidence`].
+    async fn locate_within(&self, description: &str, prompts: &[String], region: Option<Region>) -> Result<(u32, u32), DaemonError> {
+        let screenshot = self.screenshot_within(region).await?;
+
+        self.locate_in(&screenshot, description, prompts).await
+    }
+
+    /// Locates an element like [`Daemon::locate_within`] on a screenshot which was already taken.
+    async fn locate_in(&self, screenshot: &(Bytes, Region), description: &str, prompts: &[String]) -> Result<(u32, u32), DaemonError> {
+        if let Some(verification) = &self.settings.verification {
+            let vote = self.vote_in(screenshot, verification, prompts).await?;
+            if vote.confidence < verification.min_confidence {
+                return Err(DaemonError::LowConfidence { selector: description.to_string(), confidence: vote.confidence });
             }
+            return Ok(vote.point);
         }
 
-        if visible_text.is_empty() {
-            return Err(DaemonError::Unexpected("No visible text found".to_string()));
-        }
+        let (screenshot_bytes, region) = screenshot;
+
+        let parsed_coords = self.vision_backend().locate(screenshot_bytes, &prompts[0]).await?;
 
-        Ok(visible_text)
+        let (x, y) = self.calculate_coordinates(parsed_coords, region.width, region.height)?;
+        let pixel_coordinates = (region.x + x, region.y + y);
+        log!("pixel_coordinates: {:?}", pixel_coordinates);
+        log!();
+        Ok(pixel_coordinates)
     }
 
-    pub async fn coordinate_of_from_prompt(&self, prompt: &str) -> Result<(u32, u32), DaemonError> {
-        println!();
-        println!("prompt: {}", prompt);
-        let screenshot_bytes = self.screenshot().await?;
+    /// Locates an element once per sample on the screenshot, rotating through the prompts and
+    /// backends, and votes on the results. Fails with the first error if every sample failed.
+    async fn vote_in(&self, screenshot: &(Bytes, Region), verification: &Verification, prompts: &[String]) -> Result<Vote, DaemonError> {
+        let (screenshot_bytes, region) = screenshot;
+        let primary = self.vision_backend();
+        let deterministic = self.vision_backend.is_none() && self.settings.temperature <= 0.0;
+        let sample_count = verification.samples(deterministic);
+
+        let samples = (0..sample_count).map(|sample| {
+            let backend = verification.backend(sample, &primary);
+            let prompt = &prompts[sample % prompts.len()];
+            async move { backend.locate(screenshot_bytes, prompt).await }
+        });
 
-        let img = ImageReader::with_format(std::io::Cursor::new(&screenshot_bytes), ImageFormat::Png)
-            .decode()?;
-        let (width, height) = (img.width(), img.height());
-        let image_base64 = general_purpose::STANDARD.encode(&screenshot_bytes);
-
-        let request_body = json!({
-            "model": "allenai/Molmo-7B-D-0924",
-            "messages": [
-                {
-                    "role": "user",
-                    "content": [
-                        {"type": "text", "text": prompt},
-                        {"type": "image_url", "image_url": {"url": format!("data:image/jpeg;base64,{}", image_base64)}}
-                    ]
+        let mut points = Vec::new();
+        let mut first_error = None;
+        for result in join_all(samples).await {
+            match result.and_then(|parsed_coords| self.calculate_coordinates(parsed_coords, region.width, region.height)) {
+                Ok((x, y)) => points.push((region.x + x, region.y + y)),
+                Err(e) => {
+                    log!("Sample failed: {}", e);
+                    first_error.get_or_insert(e);
                 }
-            ],
-            "temperature": 0,
-            "top_k": 1
-        });
+            }
+        }
 
-        let response = self.client
-            .post(format!("{}/v1/chat/completions", self.settings.vision_llm_url))
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.settings.vision_llm_auth_token))
-            .json(&request_body)
-            .send()
-            .await?;
+        let Some(vote) = vote(&points, sample_count, verification.cluster_radius) else {
+            return Err(first_error.expect("A sample without a point failed"));
+        };
+        log!("samples: {:?}, vote: {:?}", points, vote);
+        log!();
+        Ok(vote)
+    }
 
-        if !response.status().is_success() {
-            return Err(DaemonError::Unexpected("Failed to process the find request.".into()));
-        }
+    /// Returns the screen coordinates of every element identified by the selector, ordered
+    /// top-to-bottom and then left-to-right, or an empty list if there are none.
+    pub async fn coordinates_of(&self, selector: &str) -> Result<Vec<(u32, u32)>, DaemonError> {
+        let prompt = format!("{} {}", self.settings.vision_all_coordinates_prompt, selector);
+        let (screenshot_bytes, region) = self.screenshot_within(None).await?;
+
+        let parsed_coords = self.vision_backend().locate_all(&screenshot_bytes, &prompt).await?;
+
+        let mut pixel_coordinates = parsed_coords.into_iter()
+            .map(|coords| self.calculate_coordinates(coords, region.width, region.height))
+            .collect::<Result<Vec<_>, _>>()?;
+        sort_in_reading_order(&mut pixel_coordinates);
+        log!("pixel_coordinates: {:?}", pixel_coordinates);
+        log!();
+        Ok(pixel_coordinates)
+    }
 
-        let response_body: FindResponse = response.json().await?;
-        let response_text = serde_json::to_string(&response_body)?;
-        println!("Full response body: {}", response_text);
+    /// Returns the box on the screen covered by the element identified by the selector, found as
+    /// set with [`DaemonSettings::set_box_mode`].
+    pub async fn element_box(&self, selector: &str) -> Result<ElementBox, DaemonError> {
+        let screenshot = self.screenshot_within(None).await?;
 
-        let content = response_body.choices.first()
-            .ok_or_else(|| DaemonError::Unexpected(format!("No choices in response. Prompt: {}", prompt)))?
-            .message.content.trim();
+        self.element_box_in(&screenshot, selector).await
+    }
 
-        let parsed_coords = self.parse_coordinates(content)?;
+    /// Returns a PNG screenshot of just the element identified by the selector, cropped from the
+    /// same screenshot its box was found on.
+    pub async fn element_screenshot(&self, selector: &str) -> Result<Bytes, DaemonError> {
+        let screenshot = self.screenshot_within(None).await?;
+        let element_box = self.element_box_in(&screenshot, selector).await?;
+        let (cropped, _) = crop(&screenshot.0, element_box)?;
 
-        let pixel_coordinates = self.calculate_coordinates(parsed_coords, width, height);
-        println!("pixel_coordinates: {:?}", pixel_coordinates);
-        println!();
-        pixel_coordinates
+        Ok(cropped)
     }
 
-    pub async fn coordinate_of(&self, selector: &str) -> Result<(u32, u32), DaemonError> {
-        let prompt = format!("{} {}", self.settings.vision_coordinate_prompt, selector);
-        self.coordinate_of_from_prompt(&prompt).await
+    async fn element_box_in(&self, screenshot: &(Bytes, Region), selector: &str) -> Result<ElementBox, DaemonError> {
+        match self.settings.box_mode {
+            BoxMode::Corners => self.element_box_from_corners(screenshot, selector).await,
+            BoxMode::Segmentation => self.element_box_from_segmentation(screenshot, selector).await,
+        }
     }
 
-    fn parse_coordinates(&self, content: &str) -> Result<(f64, f64), DaemonError> {
-        let re_xml = Regex::new(r#"x\d*="\s*([0-9]+(?:\.[0-9]+)?)"\s+y\d*="\s*([0-9]+(?:\.[0-9]+)?)"#).unwrap();
-        let re_parens = Regex::new(r#"\(?\s*(\d+(?:\.\d+)?)\s*,\s*(\d+(?:\.\d+)?)\s*\)?"#).unwrap();
-        let mut all_points = Vec::new();
+    async fn element_box_from_corners(&self, screenshot: &(Bytes, Region), selector: &str) -> Result<ElementBox, DaemonError> {
+        let locate_corner = |corner: &'static str| {
+            let prompt = format!("{} {}", self.settings.vision_corner_prompt.replace("{corner}", corner), selector);
+            async move { self.locate_in(screenshot, &format!("{} corner of {}", corner, selector), &[prompt]).await }
+        };
+        let (left, top) = locate_corner("top left").await?;
+        let (right, bottom) = locate_corner("bottom right").await?;
+
+        if right <= left || bottom <= top {
+            return Err(DaemonError::FailedToParseCoordinates(format!(
+                "Corners ({}, {}) and ({}, {}) of '{}' don't form a box",
+                left, top, right, bottom, selector
+            )));
+        }
 
+        Ok(ElementBox::new(left, top, right - left, bottom - top))
+    }
 
-        for cap in re_xml.captures_iter(content) {
-            if let (Some(x), Some(y)) = (cap.get(1), cap.get(2)) {
-                if let (Ok(x), Ok(y)) = (x.as_str().parse::<f64>(), y.as_str().parse::<f64>()) {
-                    if x <= 100.0 && y <= 100.0 {
-                        all_points.push((x, y));
-                    }
-                }
-            }
-        }
+    async fn element_box_from_segmentation(&self, screenshot: &(Bytes, Region), selector: &str) -> Result<ElementBox, DaemonError> {
+        let prompt = format!("{} {}", self.settings.vision_coordinate_prompt, selector);
+        let (screenshot_bytes, region) = screenshot;
 
-        for cap in re_parens.captures_iter(content) {
-            if let (Some(x), Some(y)) = (cap.get(1), cap.get(2)) {
-                if let (Ok(x), Ok(y)) = (x.as_str().parse::<f64>(), y.as_str().parse::<f64>()) {
-                    if x <= 100.0 && y <= 100.0 {
-                        all_points.push((x, y));
-                    }
-                }
-            }
-        }
+        let parsed_coords = self.vision_backend().locate(screenshot_bytes, &prompt).await?;
+        let (x, y) = self.calculate_coordinates(parsed_coords, region.width, region.height)?;
 
+        let image = image::load_from_memory_with_format(screenshot_bytes, ImageFormat::Png)?.to_rgb8();
+        let element_box = segment(&image, x, y).ok_or_else(|| DaemonError::FailedToParseCoordinates(format!(
+            "No element around ({}, {}) for '{}'", x, y, selector
+        )))?;
+        log!("element_box: {:?}", element_box);
+        Ok(element_box)
+    }
 
-        if all_points.is_empty() {
-            Err(DaemonError::FailedToParseCoordinates(String::from(content)))
-        } else {
-            let option = *all_points.first().unwrap();
-            Ok(option)
+    /// Takes a screenshot and, when a region is given, crops it to that region. Returns the image
+    /// with the region of the screen it covers.
+    async fn screenshot_within(&self, region: Option<Region>) -> Result<(Bytes, Region), DaemonError> {
+        let screenshot_bytes = self.screenshot().await?;
+        if let Some(region) = region {
+            return crop(&screenshot_bytes, region);
         }
+
+        let (width, height) = ImageReader::with_format(std::io::Cursor::new(&screenshot_bytes), ImageFormat::Png)
+            .into_dimensions()?;
+        Ok((screenshot_bytes, Region::new(0, 0, width, height)))
     }
 
     fn calculate_coordinates(&self, coords: (f64, f64), width: u32, height: u32) -> Result<(u32, u32), DaemonError> {
@@ -379,8 +697,51 @@ impl Daemon {
     }
 
     pub async fn click(&self, selector: &str) -> Result<(), DaemonError> {
+        self.click_with_options(selector, &ClickOptions::new()).await
+    }
+
+    pub async fn click_with_options(&self, selector: &str, options: &ClickOptions) -> Result<(), DaemonError> {
         let coordinate = self.coordinate_of(selector).await?;
-        self.click_coordinate(coordinate.0, coordinate.1).await
+        self.click_coordinate(coordinate.0, coordinate.1, options).await
+    }
+}
+
+/// Sorts points top-to-bottom and then left-to-right. Points less than [`ROW_TOLERANCE`] pixels
+/// below the first point of a row belong to that row, since the model rarely places the points of
+/// elements in one row at exactly the same height.
+fn sort_in_reading_order(points: &mut [(u32, u32)]) {
+    points.sort_by_key(|&(x, y)| (y, x));
+
+    let mut row_start = 0;
+    for i in 1..=points.len() {
+        if i == points.len() || points[i].1 - points[row_start].1 >= ROW_TOLERANCE {
+            points[row_start..i].sort_by_key(|&(x, _)| x);
+            row_start = i;
+        }
+    }
+}
+
+/// Crops a PNG screenshot to the region, clamped to the screen. Returns the image with the region
+/// of the screen it covers.
+fn crop(screenshot_bytes: &Bytes, region: Region) -> Result<(Bytes, Region), DaemonError> {
+    let image = image::load_from_memory_with_format(screenshot_bytes, ImageFormat::Png)?;
+    let region = region.clamp(image.width(), image.height())
+        .ok_or_else(|| DaemonError::RegionOutOfBounds(format!("{:?} on a {}x{} screen", region, image.width(), image.height())))?;
+
+    let cropped = image.crop_imm(region.x, region.y, region.width, region.height);
+    let mut buffer = Vec::new();
+    cropped.write_to(&mut std::io::Cursor::new(&mut buffer), ImageFormat::Png)?;
+
+    Ok((Bytes::from(buffer), region))
+}
+
+/// Names an element located with a raw prompt in errors by the prompt's last line, which is
+/// where the selector usually is, shortened to 60 characters.
+fn describe_prompt(prompt: &str) -> String {
+    let line = prompt.trim().lines().last().unwrap_or_default().trim();
+    match line.char_indices().nth(60) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line.to_string(),
     }
 }
 
@@ -389,41 +750,19 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_coordinates() {
-        let daemon = Daemon::new("https://doesnotmatter.com");
-        let input = r#"<points x1="0.6" y1="13.0" x2="104.7" y2="12.8" alt="the point coordinate of the sign up name input field">the point coordinate of the sign up name input field</points>"#;
-        let result = daemon.parse_coordinates(input).unwrap();
-        assert_eq!(result, (0.6, 13.0));
-    }
+    fn test_sort_in_reading_order() {
+        let mut points = vec![(300, 102), (100, 200), (500, 98), (100, 100), (300, 205)];
 
-    #[test]
-    fn test_parse_coordinates_in_parens() {
-        let daemon = Daemon::new("https://doesnotmatter.com");
-        let input = r#"The center of the name input field is at coordinates (10.9, 14.1) in the image. This point represents the midpoint of the horizontal rectangle that contains the input field for the user's name.""#;
-        let result = daemon.parse_coordinates(input).unwrap();
-        assert_eq!(result, (10.9, 14.1));
-    }
+        sort_in_reading_order(&mut points);
 
-    #[test]
-    fn test_parse_the_visible_text() {
-        let daemon = Daemon::new("https://doesnotmatter.com");
-        let input = r#" Here's an array list containing all the text visible on the screen:\n\n[\n  \"host.docker.internal:3001\",\n  \"Firefox Privacy Notice\",\n  \"End-to-End Test\",\n  \"Name\",\n  \"Phone\",\n  \"Email\",\n  \"First Programmer\",\n  \"Analytical Engine\",\n  \"Programming\",\n  \"Submit\"\n]\n\nThis list includes all the text elements present in the browser tab, address bar, form fields, and buttons visible in the image."#;
-        let result = daemon.parse_visible_text(input).unwrap();
-        assert_eq!(result, vec![
-            "host.docker.internal:3001",
-            "Firefox Privacy Notice",
-            "End-to-End Test",
-            "Name",
-            "Phone",
-            "Email",
-            "First Programmer",
-            "Analytical Engine",
-            "Programming",
-            "Submit"
-        ]);
+        assert_eq!(points, vec![(100, 100), (300, 102), (500, 98), (100, 200), (300, 205)]);
     }
 
+    #[test]
+    fn test_describe_prompt() {
+        let prompt = format!("You are a UI assistant.\nReply with a point.\nPoint to the {}", "x".repeat(60));
 
+        assert_eq!(describe_prompt("Point to the submit button\n"), "Point to the submit button");
+        assert_eq!(describe_prompt(&prompt), format!("Point to the {}...", "x".repeat(47)));
+    }
 }
-
-
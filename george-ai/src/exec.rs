@@ -0,0 +1,281 @@
+use crate::virtual_machine::VirtualMachineError;
+use bollard::container::LogOutput;
+use bollard::exec::{CreateExecOptions, StartExecOptions, StartExecResults};
+use bollard::Docker;
+use futures_util::StreamExt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Describes how a command is run in the virtual machine.
+///
+/// # Example
+///
+/// ```rust
+/// use george_ai::ExecOptions;
+/// use std::time::Duration;
+///
+/// let options = ExecOptions::new()
+///     .set_timeout(Duration::from_secs(30))
+///     .set_working_dir(String::from("/root/app"))
+///     .add_env(String::from("NODE_ENV"), String::from("test"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExecOptions {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) env: Vec<(String, String)>,
+    pub(crate) working_dir: Option<String>,
+    pub(crate) discard_output: bool,
+}
+
+impl ExecOptions {
+    /// Runs in the container's working directory and environment without a timeout, capturing
+    /// the output.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Kills the command and fails with [`VirtualMachineError::ExecTimeout`] if it runs longer
+    /// than this. Background processes aren't affected.
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets an environment variable for the command, in addition to the container's.
+    pub fn add_env(mut self, name: String, value: String) -> Self {
+        self.env.push((name, value));
+        self
+    }
+
+    /// Sets the directory the command runs in.
+    pub fn set_working_dir(mut self, working_dir: String) -> Self {
+        self.working_dir = Some(working_dir);
+        self
+    }
+
+    /// Runs the command detached without capturing its output, e.g. for a long-running process
+    /// which prints a lot, such as a browser. Its stdout and stderr are then always empty.
+    pub fn set_discard_output(mut self, discard_output: bool) -> Self {
+        self.discard_output = discard_output;
+        self
+    }
+
+    fn create_exec_options(&self, command: &str) -> CreateExecOptions<String> {
+        let env = self.env.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+
+        CreateExecOptions {
+            cmd: Some(vec![String::from("sh"), String::from("-c"), command.to_string()]),
+            attach_stdout: Some(!self.discard_output),
+            attach_stderr: Some(!self.discard_output),
+            env: Some(env),
+            working_dir: self.working_dir.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The result of a command which ran to completion.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecOutput {
+    /// The command's exit code, or `None` if Docker didn't report one.
+    pub exit_code: Option<i64>,
+    pub stdout: String,
+    pub stderr: String,
+    /// How long the command ran for.
+    pub duration: Duration,
+}
+
+impl ExecOutput {
+    /// Whether the command exited with code 0.
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+#[derive(Default)]
+struct CapturedOutput {
+    stdout: String,
+    stderr: String,
+}
+
+impl CapturedOutput {
+    fn push(&mut self, chunk: LogOutput) {
+        match chunk {
+            LogOutput::StdErr { message } => self.stderr.push_str(&String::from_utf8_lossy(&message)),
+            LogOutput::StdOut { message } | LogOutput::Console { message } => {
+                self.stdout.push_str(&String::from_utf8_lossy(&message));
+            }
+            LogOutput::StdIn { .. } => {}
+        }
+    }
+}
+
+/// A command running in the background in the virtual machine, e.g. a dev server or a browser.
+///
+/// Its output is captured while it runs, unless [`ExecOptions::set_discard_output`] is set.
+/// Dropping the handle leaves the process running.
+pub struct BackgroundProcess {
+    docker: Docker,
+    container_name: String,
+    exec_id: String,
+    command: String,
+    started: Instant,
+    output: Arc<Mutex<CapturedOutput>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl BackgroundProcess {
+    pub(crate) async fn start(docker: &Docker, container_name: &str, command: &str, options: &ExecOptions) -> Result<Self, VirtualMachineError> {
+        let exec = docker.create_exec(container_name, options.create_exec_options(command)).await?;
+
+        let started = Instant::now();
+        let output = Arc::new(Mutex::new(CapturedOutput::default()));
+        let start_options = StartExecOptions { detach: options.discard_output, ..Default::default() };
+        let reader = match docker.start_exec(&exec.id, Some(start_options)).await? {
+            StartExecResults::Attached { output: mut stream, .. } => {
+                let output = output.clone();
+                Some(tokio::spawn(async move {
+                    while let Some(Ok(chunk)) = stream.next().await {
+                        output.lock().unwrap().push(chunk);
+                    }
+                }))
+            }
+            StartExecResults::Detached => None,
+        };
+
+        Ok(Self {
+            docker: docker.clone(),
+            container_name: container_name.to_string(),
+            exec_id: exec.id,
+            command: command.to_string(),
+            started,
+            output,
+            reader,
+        })
+    }
+
+    /// The command this process is running.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub async fn is_running(&self) -> Result<bool, VirtualMachineError> {
+        let exec = self.docker.inspect_exec(&self.exec_id).await?;
+
+        Ok(exec.running.unwrap_or(false))
+    }
+
+    /// Everything the process has written to stdout so far.
+    pub fn stdout(&self) -> String {
+        self.output.lock().unwrap().stdout.clone()
+    }
+
+    /// Everything the process has written to stderr so far.
+    pub fn stderr(&self) -> String {
+        self.output.lock().unwrap().stderr.clone()
+    }
+
+    /// Waits for the process to exit and returns its output.
+    pub async fn wait(&mut self) -> Result<ExecOutput, VirtualMachineError> {
+        if let Some(reader) = self.reader.take() {
+            reader.await.map_err(|e| VirtualMachineError::Io(std::io::Error::other(e)))?;
+        }
+
+        // The output closes a moment before Docker records the exit code.
+        let exit_code = loop {
+            let exec = self.docker.inspect_exec(&self.exec_id).await?;
+            if !exec.running.unwrap_or(false) {
+                break exec.exit_code;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+
+        let output = self.output.lock().unwrap();
+        Ok(ExecOutput {
+            exit_code,
+            stdout: output.stdout.clone(),
+            stderr: output.stderr.clone(),
+            duration: self.started.elapsed(),
+        })
+    }
+
+    /// Sends SIGTERM to the process and its children.
+    pub async fn kill(&self) -> Result<(), VirtualMachineError> {
+        let exec = self.docker.inspect_exec(&self.exec_id).await?;
+        let Some(pid) = exec.pid.filter(|_| exec.running.unwrap_or(false)) else {
+            return Ok(());
+        };
+
+        let command = format!("pkill -TERM -P {pid}; kill -TERM {pid}");
+        let mut kill = Self::start(&self.docker, &self.container_name, &command, &ExecOptions::new()).await?;
+        kill.wait().await?;
+
+        Ok(())
+    }
+
+    /// Waits for the process like [`BackgroundProcess::wait`], killing it if it doesn't exit
+    /// within `timeout`.
+    pub(crate) async fn wait_with_timeout(&mut self, timeout: Option<Duration>) -> Result<ExecOutput, VirtualMachineError> {
+        let Some(timeout) = timeout else {
+            return self.wait().await;
+        };
+
+        match tokio::time::timeout(timeout, self.wait()).await {
+            Ok(output) => output,
+            Err(_) => {
+                self.kill().await?;
+                Err(VirtualMachineError::ExecTimeout { command: self.command.clone(), timeout })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_success() {
+        let output = |exit_code| ExecOutput {
+            exit_code,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration: Duration::ZERO,
+        };
+
+        assert!(output(Some(0)).success());
+        assert!(!output(Some(1)).success());
+        assert!(!output(None).success());
+    }
+
+    #[test]
+    fn test_create_exec_options() {
+        let options = ExecOptions::new()
+            .add_env(String::from("NODE_ENV"), String::from("test"))
+            .set_working_dir(String::from("/root/app"));
+
+        let create_options = options.create_exec_options("npm test");
+
+        assert_eq!(create_options.cmd, Some(vec![String::from("sh"), String::from("-c"), String::from("npm test")]));
+        assert_eq!(create_options.env, Some(vec![String::from("NODE_ENV=test")]));
+        assert_eq!(create_options.working_dir.as_deref(), Some("/root/app"));
+        assert_eq!(create_options.attach_stdout, Some(true));
+
+        let discarded = ExecOptions::new().set_discard_output(true).create_exec_options("google-chrome");
+        assert_eq!((discarded.attach_stdout, discarded.attach_stderr), (Some(false), Some(false)));
+    }
+
+    #[test]
+    fn test_captured_output_separates_streams() {
+        let mut output = CapturedOutput::default();
+
+        output.push(LogOutput::StdOut { message: "Listening on ".into() });
+        output.push(LogOutput::StdErr { message: "warning\n".into() });
+        output.push(LogOutput::Console { message: "port 3000\n".into() });
+
+        assert_eq!(output.stdout, "Listening on port 3000\n");
+        assert_eq!(output.stderr, "warning\n");
+    }
+}
@@ -0,0 +1,161 @@
+use crate::daemon::DaemonError;
+use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use std::io::Cursor;
+
+/// How a screenshot is encoded before it is sent to the vision model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageEncoding {
+    Png,
+    /// Lossy JPEG with a quality from 1 to 100. This is the only encoding with a quality.
+    Jpeg { quality: u8 },
+    /// Lossless WebP, which is usually smaller than PNG. There is no quality setting since the
+    /// encoder is lossless only.
+    WebP,
+}
+
+/// Processes screenshots before they are sent to the vision model, trading detail for latency
+/// and bandwidth. The default sends screenshots untouched.
+///
+/// Points are returned by the model as percentages of the image, so they still map back to
+/// screen pixels after the image has been resized.
+///
+/// # Example
+///
+/// ```rust
+/// use george_ai::{DaemonSettings, ImageEncoding, ImageOptions};
+///
+/// let settings = DaemonSettings::new("https://your-molmo-llm.com").set_image_options(
+///     ImageOptions::new()
+///         .set_max_dimension(1024)
+///         .set_encoding(ImageEncoding::Jpeg { quality: 80 })
+///         .set_grayscale(true),
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImageOptions {
+    max_dimension: Option<u32>,
+    encoding: Option<ImageEncoding>,
+    grayscale: bool,
+}
+
+impl ImageOptions {
+    /// Sends screenshots at full resolution, in color and in the format the daemon returned.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shrinks screenshots so neither side is larger than `max_dimension` pixels, keeping the
+    /// aspect ratio. Smaller screenshots are left alone.
+    pub fn set_max_dimension(mut self, max_dimension: u32) -> Self {
+        self.max_dimension = Some(max_dimension);
+        self
+    }
+
+    /// Re-encodes screenshots in the given format.
+    pub fn set_encoding(mut self, encoding: ImageEncoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Converts screenshots to grayscale.
+    pub fn set_grayscale(mut self, grayscale: bool) -> Self {
+        self.grayscale = grayscale;
+        self
+    }
+
+    /// Applies the options to an encoded image and returns the new image with its MIME type,
+    /// e.g. for a custom [`crate::VisionBackend`] that wants the same processing.
+    pub fn apply(&self, image: &Bytes) -> Result<(Bytes, &'static str), DaemonError> {
+        let format = image::guess_format(image)?;
+        if self.max_dimension.is_none() && self.encoding.is_none() && !self.grayscale {
+            return Ok((image.clone(), format.to_mime_type()));
+        }
+
+        let mut decoded = image::load_from_memory_with_format(image, format)?;
+        if self.grayscale {
+            decoded = DynamicImage::ImageLuma8(decoded.to_luma8());
+        }
+        if let Some(max_dimension) = self.max_dimension {
+            if decoded.width() > max_dimension || decoded.height() > max_dimension {
+                decoded = decoded.resize(max_dimension, max_dimension, FilterType::Triangle);
+            }
+        }
+
+        let encoding = self.encoding.unwrap_or(match format {
+            ImageFormat::Jpeg => ImageEncoding::Jpeg { quality: 90 },
+            ImageFormat::WebP => ImageEncoding::WebP,
+            _ => ImageEncoding::Png,
+        });
+
+        let mut buffer = Vec::new();
+        let mime_type = match encoding {
+            ImageEncoding::Png => {
+                decoded.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png)?;
+                ImageFormat::Png.to_mime_type()
+            }
+            ImageEncoding::Jpeg { quality } => {
+                // JPEG has no alpha channel.
+                let decoded = if self.grayscale {
+                    DynamicImage::ImageLuma8(decoded.to_luma8())
+                } else {
+                    DynamicImage::ImageRgb8(decoded.to_rgb8())
+                };
+                decoded.write_with_encoder(JpegEncoder::new_with_quality(&mut buffer, quality.clamp(1, 100)))?;
+                ImageFormat::Jpeg.to_mime_type()
+            }
+            ImageEncoding::WebP => {
+                // The WebP encoder only supports 8-bit RGB(A) images.
+                DynamicImage::ImageRgba8(decoded.to_rgba8()).write_to(&mut Cursor::new(&mut buffer), ImageFormat::WebP)?;
+                ImageFormat::WebP.to_mime_type()
+            }
+        };
+
+        Ok((Bytes::from(buffer), mime_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn png(width: u32, height: u32) -> Bytes {
+        let image = RgbaImage::from_pixel(width, height, Rgba([200, 40, 40, 255]));
+        let mut buffer = Vec::new();
+        image.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png).unwrap();
+        Bytes::from(buffer)
+    }
+
+    #[test]
+    fn test_default_options_keep_the_image() {
+        let image = png(40, 30);
+
+        assert_eq!(ImageOptions::new().apply(&image).unwrap(), (image, "image/png"));
+    }
+
+    #[test]
+    fn test_resize_and_encode_as_jpeg() {
+        let options = ImageOptions::new()
+            .set_max_dimension(20)
+            .set_encoding(ImageEncoding::Jpeg { quality: 70 })
+            .set_grayscale(true);
+
+        let (image, mime_type) = options.apply(&png(40, 30)).unwrap();
+        let decoded = image::load_from_memory(&image).unwrap();
+
+        assert_eq!(mime_type, "image/jpeg");
+        assert_eq!((decoded.width(), decoded.height()), (20, 15));
+        assert_eq!(decoded.color(), image::ColorType::L8);
+    }
+
+    #[test]
+    fn test_encode_as_webp() {
+        let (image, mime_type) = ImageOptions::new().set_encoding(ImageEncoding::WebP).apply(&png(40, 30)).unwrap();
+
+        assert_eq!(mime_type, "image/webp");
+        assert_eq!(image::guess_format(&image).unwrap(), ImageFormat::WebP);
+    }
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Middle,
+    Right,
+}
+
+/// A key held down while clicking.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Modifier {
+    Control,
+    Shift,
+    Alt,
+    Meta,
+}
+
+/// Describes how an element is clicked.
+///
+/// # Example
+///
+/// ```rust
+/// use george_ai::{ClickOptions, Modifier, MouseButton};
+///
+/// let options = ClickOptions::new()
+///     .set_button(MouseButton::Left)
+///     .set_count(2)
+///     .add_modifier(Modifier::Shift);
+/// ```
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ClickOptions {
+    button: MouseButton,
+    count: u32,
+    modifiers: Vec<Modifier>,
+}
+
+impl Default for ClickOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClickOptions {
+    /// A single left click without modifiers.
+    pub fn new() -> Self {
+        Self {
+            button: MouseButton::Left,
+            count: 1,
+            modifiers: Vec::new(),
+        }
+    }
+
+    pub fn set_button(mut self, button: MouseButton) -> Self {
+        self.button = button;
+        self
+    }
+
+    /// Sets how many times the button is clicked, e.g. `2` for a double click.
+    pub fn set_count(mut self, count: u32) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Holds the modifier down for the duration of the click.
+    pub fn add_modifier(mut self, modifier: Modifier) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+}
+
+/// Whether a chord of keys is pressed and released, only pressed or only released.
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyDirection {
+    #[default]
+    Click,
+    Down,
+    Up,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollAxis {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+/// Describes how an element is dragged.
+///
+/// The mouse is moved in `steps` evenly spaced moves, `step_delay` apart, so drag and drop
+/// libraries which listen for mousemove events see the drag happen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DragOptions {
+    pub(crate) button: MouseButton,
+    pub(crate) steps: u32,
+    pub(crate) step_delay: Duration,
+}
+
+impl Default for DragOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DragOptions {
+    /// Drags with the left button in 10 steps, 10 milliseconds apart.
+    pub fn new() -> Self {
+        Self {
+            button: MouseButton::Left,
+            steps: 10,
+            step_delay: Duration::from_millis(10),
+        }
+    }
+
+    pub fn set_button(mut self, button: MouseButton) -> Self {
+        self.button = button;
+        self
+    }
+
+    /// Sets how many intermediate moves are made between the two points.
+    pub fn set_steps(mut self, steps: u32) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Sets how long to wait between moves.
+    pub fn set_step_delay(mut self, step_delay: Duration) -> Self {
+        self.step_delay = step_delay;
+        self
+    }
+}
+
+/// Describes how an option is chosen from a dropdown by [`crate::George::select_with_options`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectOptions {
+    pub(crate) render_timeout: Duration,
+    pub(crate) max_scrolls: u32,
+    pub(crate) verify: bool,
+}
+
+impl Default for SelectOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelectOptions {
+    /// Waits up to 2 seconds for the options to render, scrolls the list up to 5 times to find the
+    /// option and doesn't verify the selection.
+    pub fn new() -> Self {
+        Self {
+            render_timeout: Duration::from_secs(2),
+            max_scrolls: 5,
+            verify: false,
+        }
+    }
+
+    /// Sets how long to wait for the option list to render before scrolling it.
+    pub fn set_render_timeout(mut self, render_timeout: Duration) -> Self {
+        self.render_timeout = render_timeout;
+        self
+    }
+
+    /// Sets how many times to scroll the option list looking for the option.
+    pub fn set_max_scrolls(mut self, max_scrolls: u32) -> Self {
+        self.max_scrolls = max_scrolls;
+        self
+    }
+
+    /// Checks the dropdown shows the chosen option once it has closed, looking only at the
+    /// dropdown itself rather than the whole screen.
+    pub fn set_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+}
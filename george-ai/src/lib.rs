@@ -24,21 +24,58 @@
 //!     Ok(())
 //! }
 //! ```
+mod config;
 mod daemon;
+mod exec;
+mod image_options;
+mod input;
+mod logging;
+mod pool;
+mod retry_policy;
+mod scope;
+mod segmentation;
+mod verification;
 mod virtual_machine;
+mod vision;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
-use crate::daemon::{Daemon, DaemonError, DaemonSettings};
-use crate::virtual_machine::{VirtualMachine, VirtualMachineError};
+pub use crate::config::{ConfigError, GeorgeConfig};
+pub use crate::daemon::{BoxMode, Daemon, DaemonError, DaemonSettings};
+pub use crate::exec::{BackgroundProcess, ExecOptions, ExecOutput};
+pub use crate::image_options::{ImageEncoding, ImageOptions};
+pub use crate::input::{ClickOptions, DragOptions, KeyDirection, Modifier, MouseButton, ScrollAxis, SelectOptions};
+pub use crate::pool::{GeorgePool, PooledGeorge};
+pub use crate::retry_policy::{Backoff, RetryPolicy};
+pub use crate::scope::{Area, ElementBox, Region, Scope};
+pub use crate::verification::{Verification, Vote};
+pub use crate::virtual_machine::{ImagePullPolicy, VirtualMachineError, VirtualMachineSettings};
+pub use crate::vision::{LimitedVisionBackend, MolmoBackend, VisionBackend};
+use crate::logging::log;
+use crate::virtual_machine::VirtualMachine;
 use bytes::Bytes;
 use std::error::Error;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::{sleep, Instant};
 use uuid::Uuid;
 
+/// How many wheel clicks to scroll between checks in the `scroll_until_*` methods.
+const SCROLL_UNTIL_VISIBLE_AMOUNT: i32 = 5;
+
+/// How many wheel clicks to scroll an open dropdown list between checks in `select`.
+const SELECT_SCROLL_AMOUNT: i32 = 3;
+
+/// How long `reset` may take to close Chrome and delete its profile before it gives up.
+const RESET_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub struct George {
     pub daemon: Daemon,
     pub id: Uuid,
-    virtual_machine: VirtualMachine,
+    virtual_machine: Option<VirtualMachine>,
+    virtual_machine_settings: VirtualMachineSettings,
+    retry_policy: RetryPolicy,
 }
 
 impl George {
@@ -54,7 +91,9 @@ impl George {
         Self {
             id,
             daemon: Daemon::with_settings(daemon_settings),
-            virtual_machine: VirtualMachine::new(),
+            virtual_machine: None,
+            virtual_machine_settings: VirtualMachineSettings::new(),
+            retry_policy: RetryPolicy::new(),
         }
     }
 
@@ -78,30 +117,188 @@ impl George {
         Self {
             id,
             daemon: Daemon::with_settings(daemon_settings),
-            virtual_machine: VirtualMachine::new(),
+            virtual_machine: None,
+            virtual_machine_settings: VirtualMachineSettings::new(),
+            retry_policy: RetryPolicy::new(),
         }
     }
 
+    /// Creates a new instance of George from a [`GeorgeConfig`].
+    ///
+    /// Logging is process wide, so a logging setting in the config applies to every instance.
+    /// Configs which don't set it leave logging as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The vision, retry, display and container settings.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use george_ai::{George, GeorgeConfig};
+    /// use std::path::Path;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let config = GeorgeConfig::load(Some(Path::new("george.toml")))?;
+    ///     let mut george = George::with_config(config);
+    ///     george.start().await?;
+    ///     george.stop().await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_config(config: GeorgeConfig) -> Self {
+        if let Some(logging) = config.logging {
+            logging::set_enabled(logging);
+        }
+
+        let mut daemon = Daemon::with_settings(config.daemon_settings);
+        if let Some(vision_backend) = config.vision_backend {
+            daemon.set_vision_backend(vision_backend);
+        }
+        if let Some(permits) = config.vision_request_permits {
+            daemon.set_vision_request_permits(permits);
+        }
+
+        Self {
+            id: Uuid::new_v4(),
+            daemon,
+            virtual_machine: None,
+            virtual_machine_settings: config.virtual_machine_settings,
+            retry_policy: config.retry_policy,
+        }
+    }
+
+    /// Creates a new instance of George which interprets the screen with a custom vision backend
+    /// instead of Molmo.
+    ///
+    /// # Arguments
+    ///
+    /// * `vision_backend` - The vision backend used to locate elements and read text.
+    pub fn with_vision_backend(vision_backend: impl VisionBackend + 'static) -> Self {
+        let mut george = Self::new("");
+        george.set_vision_backend(vision_backend);
+        george
+    }
+
+    /// Replaces the vision backend used to interpret the screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `vision_backend` - The vision backend used to locate elements and read text.
+    pub fn set_vision_backend(&mut self, vision_backend: impl VisionBackend + 'static) {
+        self.daemon.set_vision_backend(Arc::new(vision_backend));
+    }
+
+    /// Sets the settings for the virtual machine, such as the display resolution. These take
+    /// effect the next time George is started.
+    ///
+    /// # Arguments
+    ///
+    /// * `virtual_machine_settings` - Settings for the Docker container George runs in.
+    pub fn set_virtual_machine_settings(&mut self, virtual_machine_settings: VirtualMachineSettings) {
+        self.virtual_machine_settings = virtual_machine_settings;
+    }
+
+    /// Sets the retry policy used by actions which aren't given their own.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry_policy` - How long and how often to retry actions.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
     /// Starts George by initializing the virtual machine and daemon.
     ///
     /// This method must be called before performing any automation tasks.  It will
     /// spin up a Docker container for you to interact with.
     pub async fn start(&mut self) -> Result<(), Box<dyn Error>> {
-        self.virtual_machine.start().await?;
+        let virtual_machine = self.virtual_machine.insert(
+            VirtualMachine::with_settings(self.virtual_machine_settings.clone())?
+        );
+        virtual_machine.start().await?;
 
-        if let Some(port) = self.virtual_machine.port.as_ref() {
+        if let Some(port) = virtual_machine.port.as_ref() {
             self.daemon.set_port(port.clone());
 
-            println!("Daemon running at http://localhost:{}", port);
+            log!("Daemon running at http://localhost:{}", port);
             Ok(self.daemon.ready().await?)
         } else {
             Err("Failed to get port from virtual machine".into())
         }
     }
 
-    /// Stops George by shutting down the docker container.
+    /// Connects George to a george-daemon which is already running instead of starting a Docker
+    /// container, e.g. a long-lived container, a VM or a Linux desktop running the daemon under
+    /// Xvfb. Docker is not used at all, so [`George::stop`] leaves the daemon running and
+    /// [`George::execute`] and the Chrome helpers built on it are unavailable.
+    ///
+    /// # Arguments
+    ///
+    /// * `daemon_url` - The URL of the daemon, e.g. `http://192.168.1.20:3000`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use george_ai::George;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut george = George::new("https://your-molmo-llm.com");
+    ///     george.connect("http://192.168.1.20:3000").await?;
+    ///     george.click("sign in button").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn connect(&mut self, daemon_url: &str) -> Result<(), DaemonError> {
+        self.daemon.set_base_url(daemon_url);
+
+        log!("Connecting to daemon at {}", daemon_url);
+        self.daemon.ready().await
+    }
+
+    /// Stops George by shutting down the docker container. A named session's container is left
+    /// running, see [`VirtualMachineSettings::set_session_name`].
     pub async fn stop(&mut self) -> Result<(), Box<dyn Error>> {
-        self.virtual_machine.stop().await
+        match self.virtual_machine.as_mut() {
+            Some(virtual_machine) => virtual_machine.stop().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Shuts down and removes the docker container, including a named session's. A named session
+    /// is removed even if this instance never started it.
+    pub async fn destroy(&mut self) -> Result<(), Box<dyn Error>> {
+        let virtual_machine = match self.virtual_machine.as_mut() {
+            Some(virtual_machine) => virtual_machine,
+            None if self.virtual_machine_settings.session_name.is_some() => self.virtual_machine.insert(
+                VirtualMachine::with_settings(self.virtual_machine_settings.clone())?
+            ),
+            None => return Ok(()),
+        };
+
+        virtual_machine.destroy().await
+    }
+
+    /// Removes the Docker containers, networks and images George created more than `older_than`
+    /// ago, e.g. ones left behind when a CI job was killed. Named sessions and images still in use
+    /// are never removed. Returns the names of the removed resources.
+    ///
+    /// Dropping a started George without stopping it only cleans up on a best-effort basis which
+    /// doesn't survive the process exiting, so run this sweep, e.g. before or after a CI job, to
+    /// make sure nothing leaks.
+    ///
+    /// The same sweep is available from the command line with
+    /// `george-ai cleanup-orphans --older-than <seconds>`.
+    ///
+    /// # Arguments
+    ///
+    /// * `older_than` - How old a resource must be to be removed.
+    pub async fn cleanup_orphans(older_than: Duration) -> Result<Vec<String>, VirtualMachineError> {
+        VirtualMachine::cleanup_orphans(older_than).await
     }
 
     /// Fills in a form field identified by the given selector with the provided text.
@@ -111,26 +308,125 @@ impl George {
     /// * `selector` - A natural language description of the form field (e.g., "input Email text field").
     /// * `with` - The text to enter into the field.
     pub async fn fill_in(&self, selector: &str, with: &str) -> Result<(), DaemonError> {
-        let timeout = Duration::from_secs(10);
-        let start = Instant::now();
-
-        while start.elapsed() < timeout {
-            match self.daemon.click(selector).await {
-                Ok(_) => return self.daemon.type_text(with).await,
-                Err(e) => {
-                    match e {
-                        DaemonError::FailedToParseCoordinates(_) => {
-                            println!("Failed to parse coordinates for selector '{}'. Retrying...", selector);
-                            sleep(Duration::from_millis(10)).await;
-                            continue;
-                        }
-                        _ => return Err(e),
-                    }
-                }
+        self.fill_in_with(selector, with, &self.retry_policy).await
+    }
+
+    /// Fills in a form field like [`George::fill_in`] using the given retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the form field (e.g., "input Email text field").
+    /// * `with` - The text to enter into the field.
+    /// * `retry_policy` - How long and how often to retry locating the field.
+    pub async fn fill_in_with(&self, selector: &str, with: &str, retry_policy: &RetryPolicy) -> Result<(), DaemonError> {
+        self.click_with(selector, retry_policy).await?;
+        self.daemon.type_text(with).await
+    }
+
+    /// Presses and releases a key or chord of keys joined by `+`.
+    ///
+    /// Key names include `enter`, `tab`, `escape`, `backspace`, `delete`, `up`, `down`, `left`,
+    /// `right`, `home`, `end`, `pageup`, `pagedown`, `f1`-`f12`, the modifiers `ctrl`, `shift`,
+    /// `alt` and `meta`, and any single character.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The key or chord to press (e.g., "enter" or "ctrl+a").
+    pub async fn press(&self, keys: &str) -> Result<(), DaemonError> {
+        self.daemon.key(keys, KeyDirection::Click).await
+    }
+
+    /// Presses a key or chord of keys and holds them down until [`George::key_up`] is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The key or chord to hold down (e.g., "shift").
+    pub async fn key_down(&self, keys: &str) -> Result<(), DaemonError> {
+        self.daemon.key(keys, KeyDirection::Down).await
+    }
+
+    /// Releases a key or chord of keys held down by [`George::key_down`].
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The key or chord to release (e.g., "shift").
+    pub async fn key_up(&self, keys: &str) -> Result<(), DaemonError> {
+        self.daemon.key(keys, KeyDirection::Up).await
+    }
+
+    /// Scrolls vertically wherever the mouse currently is.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - How many wheel clicks to scroll. Positive amounts scroll down.
+    pub async fn scroll(&self, amount: i32) -> Result<(), DaemonError> {
+        self.daemon.scroll(amount, ScrollAxis::Vertical, None).await
+    }
+
+    /// Scrolls horizontally wherever the mouse currently is.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - How many wheel clicks to scroll. Positive amounts scroll right.
+    pub async fn scroll_horizontally(&self, amount: i32) -> Result<(), DaemonError> {
+        self.daemon.scroll(amount, ScrollAxis::Horizontal, None).await
+    }
+
+    /// Scrolls vertically with the mouse over an element, e.g. a scrollable list within the page.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to scroll.
+    /// * `amount` - How many wheel clicks to scroll. Positive amounts scroll down.
+    pub async fn scroll_at(&self, selector: &str, amount: i32) -> Result<(), DaemonError> {
+        let coordinate = self.locate(selector, &self.retry_policy).await?;
+        self.daemon.scroll(amount, ScrollAxis::Vertical, Some(coordinate)).await
+    }
+
+    /// Scrolls down until the specified text is visible on the screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to scroll to.
+    /// * `max_scrolls` - How many times to scroll before giving up.
+    pub async fn scroll_until_visible(&self, text: &str, max_scrolls: u32) -> Result<(), DaemonError> {
+        for scrolls in 0..=max_scrolls {
+            match self.daemon.is_text_visible(text).await {
+                Ok(true) => return Ok(()),
+                Ok(false) | Err(DaemonError::FailedToParseExistence(_)) => {}
+                Err(e) => return Err(e),
+            }
+
+            if scrolls < max_scrolls {
+                log!("Text '{}' is not visible. Scrolling...", text);
+                self.scroll(SCROLL_UNTIL_VISIBLE_AMOUNT).await?;
             }
         }
 
-        Err(DaemonError::SelectorTimeout(String::from(selector)))
+        Err(DaemonError::NotVisibleAfterScrolling(String::from(text)))
+    }
+
+    /// Scrolls down until the vision model can locate the element identified by the selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to scroll to.
+    /// * `max_scrolls` - How many times to scroll before giving up.
+    pub async fn scroll_until_located(&self, selector: &str, max_scrolls: u32) -> Result<(u32, u32), DaemonError> {
+        for scrolls in 0..=max_scrolls {
+            match self.daemon.coordinate_of(selector).await {
+                Ok(coordinate) => return Ok(coordinate),
+                Err(DaemonError::FailedToParseCoordinates(_)) => {}
+                Err(e) => return Err(e),
+            }
+
+            if scrolls < max_scrolls {
+                log!("Selector '{}' could not be located. Scrolling...", selector);
+                self.scroll(SCROLL_UNTIL_VISIBLE_AMOUNT).await?;
+            }
+        }
+
+        Err(DaemonError::NotVisibleAfterScrolling(String::from(selector)))
     }
 
     /// Takes a screenshot of the current state of the docker container.
@@ -144,26 +440,337 @@ impl George {
     ///
     /// * `selector` - A natural language description of the element to click (e.g., "sign in button").
     pub async fn click(&self, selector: &str) -> Result<(), DaemonError> {
-        let timeout = Duration::from_secs(10);
-        let start = Instant::now();
-
-        while start.elapsed() < timeout {
-            match self.daemon.click(selector).await {
-                Ok(_) => return Ok(()),
-                Err(e) => {
-                    match e {
-                        DaemonError::FailedToParseCoordinates(_) => {
-                            println!("Failed to parse coordinates for selector '{}'. Retrying...", selector);
-                            sleep(Duration::from_millis(10)).await;
-                            continue;
-                        }
-                        _ => return Err(e),
-                    }
+        self.click_with(selector, &self.retry_policy).await
+    }
+
+    /// Clicks on an element like [`George::click`] using the given retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to click (e.g., "sign in button").
+    /// * `retry_policy` - How long and how often to retry locating the element.
+    pub async fn click_with(&self, selector: &str, retry_policy: &RetryPolicy) -> Result<(), DaemonError> {
+        self.click_with_options_and_policy(selector, &ClickOptions::new(), retry_policy).await
+    }
+
+    /// Clicks on an element identified by the given selector with a specific button, click count
+    /// and held modifiers.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to click (e.g., "sign in button").
+    /// * `options` - How the element is clicked.
+    pub async fn click_with_options(&self, selector: &str, options: &ClickOptions) -> Result<(), DaemonError> {
+        self.click_with_options_and_policy(selector, options, &self.retry_policy).await
+    }
+
+    /// Double clicks on an element identified by the given selector, e.g. to select a word.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to double click.
+    pub async fn double_click(&self, selector: &str) -> Result<(), DaemonError> {
+        self.click_with_options(selector, &ClickOptions::new().set_count(2)).await
+    }
+
+    /// Right clicks on an element identified by the given selector, e.g. to open a context menu.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to right click.
+    pub async fn right_click(&self, selector: &str) -> Result<(), DaemonError> {
+        self.click_with_options(selector, &ClickOptions::new().set_button(MouseButton::Right)).await
+    }
+
+    /// Middle clicks on an element identified by the given selector, e.g. to open a link in a new tab.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to middle click.
+    pub async fn middle_click(&self, selector: &str) -> Result<(), DaemonError> {
+        self.click_with_options(selector, &ClickOptions::new().set_button(MouseButton::Middle)).await
+    }
+
+    /// Clicks on an element while holding down the given modifiers, e.g. to multi-select rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to click.
+    /// * `modifiers` - The keys held down during the click.
+    pub async fn click_with_modifiers(&self, selector: &str, modifiers: &[Modifier]) -> Result<(), DaemonError> {
+        let options = modifiers.iter()
+            .fold(ClickOptions::new(), |options, modifier| options.add_modifier(*modifier));
+
+        self.click_with_options(selector, &options).await
+    }
+
+    /// Clicks on the nth element identified by the given selector, counting from 0 in reading
+    /// order: top-to-bottom, then left-to-right. Retries until at least `n + 1` elements are
+    /// found.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the elements (e.g., "delete button").
+    /// * `n` - Which of the elements to click, e.g. 2 for the delete button in the third row.
+    pub async fn click_nth(&self, selector: &str, n: usize) -> Result<(), DaemonError> {
+        let (x, y) = self.retry_policy.retry(
+            &format!("locate match {} of '{}'", n, selector),
+            DaemonError::SelectorTimeout(format!("{} (match {})", selector, n)),
+            || async { Ok(self.daemon.coordinates_of(selector).await?.get(n).copied()) },
+        ).await?;
+
+        self.daemon.click_coordinate(x, y, &ClickOptions::new()).await
+    }
+
+    /// Returns how many elements identified by the given selector are on the screen, e.g. to
+    /// assert the length of a list. Doesn't wait for elements to appear.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the elements (e.g., "table row").
+    pub async fn count(&self, selector: &str) -> Result<usize, DaemonError> {
+        Ok(self.daemon.coordinates_of(selector).await?.len())
+    }
+
+    /// Chooses an option from a dropdown, either a native `<select>` or a custom combobox.
+    ///
+    /// The dropdown is clicked, the option is waited for and scrolled to within the open list if
+    /// necessary, and then clicked.
+    ///
+    /// # Arguments
+    ///
+    /// * `dropdown_selector` - A natural language description of the dropdown (e.g., "Country dropdown").
+    /// * `option_text` - The text of the option to choose (e.g., "Canada").
+    pub async fn select(&self, dropdown_selector: &str, option_text: &str) -> Result<(), DaemonError> {
+        self.select_with_options(dropdown_selector, option_text, &SelectOptions::new()).await
+    }
+
+    /// Chooses an option from a dropdown like [`George::select`] with control over waiting,
+    /// scrolling and verifying the selection.
+    ///
+    /// # Arguments
+    ///
+    /// * `dropdown_selector` - A natural language description of the dropdown (e.g., "Country dropdown").
+    /// * `option_text` - The text of the option to choose (e.g., "Canada").
+    /// * `options` - How the option is found and whether the selection is verified.
+    pub async fn select_with_options(&self, dropdown_selector: &str, option_text: &str, options: &SelectOptions) -> Result<(), DaemonError> {
+        self.click(dropdown_selector).await?;
+
+        let render_policy = self.retry_policy.clone().set_timeout(options.render_timeout);
+        let mut scrolls = 0;
+        loop {
+            match self.wait_until_text_is_visible_with(option_text, &render_policy).await {
+                Ok(()) => break,
+                Err(DaemonError::TextNotVisible(_)) if scrolls < options.max_scrolls => {
+                    scrolls += 1;
+                    self.scroll_at("open dropdown list", SELECT_SCROLL_AMOUNT).await?;
                 }
+                Err(DaemonError::TextNotVisible(_)) => {
+                    log!("Option '{}' is not visible after {} scrolls", option_text, scrolls);
+                    return Err(DaemonError::NotVisibleAfterScrolling(String::from(option_text)));
+                }
+                Err(e) => return Err(e),
             }
         }
 
-        Err(DaemonError::SelectorTimeout(String::from(selector)))
+        self.click(&format!("{} option in the open dropdown list", option_text)).await?;
+
+        if options.verify {
+            // The option was already visible in the open list, so only the closed dropdown
+            // showing it confirms the selection.
+            self.retry_policy.retry(
+                &format!("verify '{}' is selected in '{}'", option_text, dropdown_selector),
+                DaemonError::SelectionNotVerified(String::from(option_text)),
+                || async {
+                    let selected = self.within(dropdown_selector, |scope| async move {
+                        scope.is_text_visible(option_text).await
+                    }).await?;
+                    Ok(selected.then_some(()))
+                },
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Drags one element onto another, e.g. a card between kanban columns or a file onto an
+    /// upload area.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_selector` - A natural language description of the element to drag.
+    /// * `to_selector` - A natural language description of where to drop it.
+    pub async fn drag_and_drop(&self, from_selector: &str, to_selector: &str) -> Result<(), DaemonError> {
+        self.drag_and_drop_with_options(from_selector, to_selector, &DragOptions::new()).await
+    }
+
+    /// Drags one element onto another like [`George::drag_and_drop`] with a specific button and
+    /// number of intermediate mouse moves.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_selector` - A natural language description of the element to drag.
+    /// * `to_selector` - A natural language description of where to drop it.
+    /// * `options` - How the element is dragged.
+    pub async fn drag_and_drop_with_options(&self, from_selector: &str, to_selector: &str, options: &DragOptions) -> Result<(), DaemonError> {
+        let from = self.locate(from_selector, &self.retry_policy).await?;
+        let to = self.locate(to_selector, &self.retry_policy).await?;
+
+        self.daemon.drag(from, to, options).await
+    }
+
+    /// Moves the mouse over an element without clicking, e.g. to reveal a tooltip or hover menu.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to hover over.
+    pub async fn hover(&self, selector: &str) -> Result<(), DaemonError> {
+        let (x, y) = self.locate(selector, &self.retry_policy).await?;
+        self.daemon.move_mouse(x, y).await
+    }
+
+    /// Samples the location of the element identified by the given selector and reports how much
+    /// the samples agree, as set with [`DaemonSettings::set_verification`]. Unlike
+    /// [`George::click`] it doesn't fail when the confidence is low.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element (e.g., "sign in button").
+    pub async fn locate_with_confidence(&self, selector: &str) -> Result<Vote, DaemonError> {
+        self.retry_policy.retry(
+            &format!("locate '{}'", selector),
+            DaemonError::SelectorTimeout(String::from(selector)),
+            || async { self.daemon.vote_on_coordinate_of(selector).await.map(Some) },
+        ).await
+    }
+
+    /// Returns the box on the screen covered by the element identified by the given selector, e.g.
+    /// to click near the right edge of an input. How it is found is set with
+    /// [`DaemonSettings::set_box_mode`].
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element (e.g., "search input").
+    pub async fn element_box(&self, selector: &str) -> Result<ElementBox, DaemonError> {
+        self.retry_policy.retry(
+            &format!("locate the box of '{}'", selector),
+            DaemonError::SelectorTimeout(String::from(selector)),
+            || async { self.daemon.element_box(selector).await.map(Some) },
+        ).await
+    }
+
+    /// Returns a PNG screenshot of just the element identified by the given selector, e.g. for
+    /// visual assertions or to see what a selector resolved to.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element (e.g., "profile picture").
+    pub async fn element_screenshot(&self, selector: &str) -> Result<Bytes, DaemonError> {
+        self.retry_policy.retry(
+            &format!("take a screenshot of '{}'", selector),
+            DaemonError::SelectorTimeout(String::from(selector)),
+            || async { self.daemon.element_screenshot(selector).await.map(Some) },
+        ).await
+    }
+
+    /// Runs `scoped` with a [`Scope`] which only looks at part of the screen, so selectors which
+    /// are ambiguous on the whole screen, like "submit button" on a page with several forms,
+    /// resolve to the element inside that part.
+    ///
+    /// # Arguments
+    ///
+    /// * `area` - A [`Region`] in screen pixels, or a selector for an element (e.g. "billing
+    ///   address form") whose region is located first.
+    /// * `scoped` - The interactions to perform within the area.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use george_ai::{George, Region};
+    ///
+    /// # async fn run(george: George) -> Result<(), george_ai::DaemonError> {
+    /// george.within("newsletter signup form", |scope| async move {
+    ///     scope.fill_in("email text field", "ada@email.com").await?;
+    ///     scope.click("submit button").await
+    /// }).await?;
+    ///
+    /// george.within(Region::new(0, 0, 1024, 80), |scope| async move {
+    ///     scope.click("search icon").await
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn within<'a, T, F, Fut>(&'a self, area: impl Into<Area>, scoped: F) -> Result<T, DaemonError>
+    where
+        F: FnOnce(Scope<'a>) -> Fut,
+        Fut: Future<Output = Result<T, DaemonError>>,
+    {
+        let region = match area.into() {
+            Area::Region(region) => region,
+            Area::Selector(selector) => self.retry_policy.retry(
+                &format!("locate the region of '{}'", selector),
+                DaemonError::SelectorTimeout(selector.clone()),
+                || async { self.daemon.element_box(&selector).await.map(Some) },
+            ).await?,
+        };
+
+        scoped(Scope::new(self, region)).await
+    }
+
+    /// Returns the width and height of the screen in pixels as reported by the daemon.
+    pub async fn display_size(&self) -> Result<(u32, u32), DaemonError> {
+        self.daemon.display_size().await
+    }
+
+    /// Returns where the mouse currently is in screen pixels, which is useful for checking where
+    /// George actually pointed.
+    pub async fn mouse_position(&self) -> Result<(i32, i32), DaemonError> {
+        self.daemon.mouse_position().await
+    }
+
+    /// Moves the mouse to an absolute screen coordinate without clicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The horizontal screen coordinate in pixels.
+    /// * `y` - The vertical screen coordinate in pixels.
+    pub async fn move_mouse(&self, x: u32, y: u32) -> Result<(), DaemonError> {
+        self.daemon.move_mouse(x, y).await
+    }
+
+    /// Presses a mouse button wherever the mouse is, without releasing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `button` - The button to press.
+    pub async fn mouse_down(&self, button: MouseButton) -> Result<(), DaemonError> {
+        self.daemon.mouse_down(button).await
+    }
+
+    /// Releases a mouse button wherever the mouse is.
+    ///
+    /// # Arguments
+    ///
+    /// * `button` - The button to release.
+    pub async fn mouse_up(&self, button: MouseButton) -> Result<(), DaemonError> {
+        self.daemon.mouse_up(button).await
+    }
+
+    /// Resolves the selector to a screen coordinate, retrying according to the retry policy.
+    async fn locate(&self, selector: &str, retry_policy: &RetryPolicy) -> Result<(u32, u32), DaemonError> {
+        retry_policy.retry(
+            &format!("locate '{}'", selector),
+            DaemonError::SelectorTimeout(String::from(selector)),
+            || async { self.daemon.coordinate_of(selector).await.map(Some) },
+        ).await
+    }
+
+    /// Locates the element, retrying according to the retry policy, and then clicks it once so a
+    /// click which reached the daemon is never sent again.
+    async fn click_with_options_and_policy(&self, selector: &str, options: &ClickOptions, retry_policy: &RetryPolicy) -> Result<(), DaemonError> {
+        let (x, y) = self.locate(selector, retry_policy).await?;
+
+        self.daemon.click_coordinate(x, y, options).await
     }
 
     /// Waits until the specified text is visible on the screen.
@@ -172,56 +779,133 @@ impl George {
     ///
     /// * `text` - The text to wait for.
     pub async fn wait_until_text_is_visible(&self, text: &str) -> Result<(), DaemonError> {
-        let timeout = Duration::from_secs(5);
-        let start = Instant::now();
+        self.wait_until_text_is_visible_with(text, &self.retry_policy).await
+    }
 
-        while start.elapsed() < timeout {
-            match self.daemon.is_text_visible(text).await {
-                Ok(result) => {
-                    match result {
-                        true => return Ok(()),
-                        false => {
-                            println!("Failed determine if text is visible '{}'. Retrying...", text);
-                            sleep(Duration::from_millis(10)).await;
-                            continue;
-                        }
-                    }
-                },
-                Err(_e) => {
-                    println!("Failed determine if text is visible '{}'. Retrying...", text);
-                    sleep(Duration::from_millis(10)).await;
-                    continue;
-                }
-            }
-        }
+    /// Waits until the specified text is visible like [`George::wait_until_text_is_visible`] using
+    /// the given retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to wait for.
+    /// * `retry_policy` - How long and how often to check for the text.
+    pub async fn wait_until_text_is_visible_with(&self, text: &str, retry_policy: &RetryPolicy) -> Result<(), DaemonError> {
+        retry_policy.retry(
+            &format!("find visible text '{}'", text),
+            DaemonError::TextNotVisible(String::from(text)),
+            || async { Ok(self.daemon.is_text_visible(text).await?.then_some(())) },
+        ).await
+    }
 
-        Err(DaemonError::Unexpected(String::from("Text is not visible")))
+    /// Runs a shell command in the virtual machine and waits for it to exit.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to run with `sh -c`.
+    pub async fn execute(&self, command: &str) -> Result<ExecOutput, VirtualMachineError> {
+        self.execute_with(command, &ExecOptions::new()).await
+    }
+
+    /// Runs a shell command like [`George::execute`] with a timeout, environment variables or
+    /// working directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to run with `sh -c`.
+    /// * `options` - How to run the command.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use george_ai::{ExecOptions, George};
+    /// use std::time::Duration;
+    ///
+    /// # async fn run(george: George) -> Result<(), Box<dyn std::error::Error>> {
+    /// let output = george.execute_with(
+    ///     "ls /root/Downloads",
+    ///     &ExecOptions::new().set_timeout(Duration::from_secs(5)),
+    /// ).await?;
+    /// assert!(output.success(), "ls failed: {}", output.stderr);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_with(&self, command: &str, options: &ExecOptions) -> Result<ExecOutput, VirtualMachineError> {
+        self.virtual_machine.as_ref()
+            .ok_or(VirtualMachineError::NotStarted)?
+            .execute(command, options).await
+    }
+
+    /// Starts a shell command in the virtual machine without waiting for it to exit. The returned
+    /// handle can poll, wait for or kill the process.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to run with `sh -c`.
+    pub async fn spawn(&self, command: &str) -> Result<BackgroundProcess, VirtualMachineError> {
+        self.spawn_with(command, &ExecOptions::new()).await
+    }
+
+    /// Starts a shell command like [`George::spawn`] with environment variables or a working
+    /// directory. The timeout is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to run with `sh -c`.
+    /// * `options` - How to run the command.
+    pub async fn spawn_with(&self, command: &str, options: &ExecOptions) -> Result<BackgroundProcess, VirtualMachineError> {
+        self.virtual_machine.as_ref()
+            .ok_or(VirtualMachineError::NotStarted)?
+            .spawn(command, options).await
     }
 
-    /// Executes a command in the virtual machine.
+    /// Copies a file or directory from this machine into the virtual machine, e.g. a fixture to
+    /// pick in an upload dialog. Parent directories are created as needed.
     ///
     /// # Arguments
     ///
-    /// * `command` - The command to execute.
-    /// * `wait_for_output` - Whether to wait for the command output.
-    pub async fn execute(&self, command: &str, wait_for_output: bool) -> Result<String, VirtualMachineError> {
-        self.virtual_machine.execute(command, wait_for_output).await
+    /// * `local_path` - The file or directory to copy.
+    /// * `container_path` - The absolute path to copy it to, e.g. `/root/fixtures/avatar.png`.
+    pub async fn upload_file(&self, local_path: impl AsRef<Path>, container_path: &str) -> Result<(), VirtualMachineError> {
+        self.virtual_machine.as_ref()
+            .ok_or(VirtualMachineError::NotStarted)?
+            .upload(local_path.as_ref(), container_path).await
+    }
+
+    /// Copies a file or directory from the virtual machine to this machine, e.g. a file the
+    /// application downloaded. Parent directories are created as needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `container_path` - The absolute path to copy, e.g. `/root/Downloads/report.pdf`.
+    /// * `local_path` - Where to copy it to.
+    pub async fn download_file(&self, container_path: &str, local_path: impl AsRef<Path>) -> Result<(), VirtualMachineError> {
+        self.virtual_machine.as_ref()
+            .ok_or(VirtualMachineError::NotStarted)?
+            .download(container_path, local_path.as_ref()).await
     }
 
     pub async fn coordinate_of_from_prompt(&self, prompt: &str) -> Result<(u32, u32), DaemonError> {
         self.daemon.coordinate_of_from_prompt(prompt).await
     }
 
+    /// Asks the vision backend a free-form question about what is currently on the screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `prompt` - The question to ask (e.g., "is there an error message on the screen?").
+    pub async fn ask(&self, prompt: &str) -> Result<String, DaemonError> {
+        self.daemon.ask(prompt).await
+    }
+
     /// Opens Chrome in the virtual machine and navigates to the specified URL.
     ///
     /// # Arguments
     ///
     /// * `url` - The URL to open in Chrome.
     pub async fn open_chrome(&self, url: &str) -> Result<(), VirtualMachineError> {
-        self.execute(
-
+        self.spawn_with(
             format!("google-chrome {} --no-sandbox --no-first-run --no-default-browser-check", url).as_str(),
-            false,
+            &ExecOptions::new().set_discard_output(true),
         ).await?;
 
         Ok(())
@@ -229,7 +913,22 @@ impl George {
 
     /// Closes Chrome in the virtual machine.
     pub async fn close_chrome(&self) -> Result<(), VirtualMachineError> {
-        self.execute("pkill google-chrome", true).await?;
+        self.execute("pkill google-chrome").await?;
+
+        Ok(())
+    }
+
+    /// Closes Chrome and deletes its profile, so the next automation in this virtual machine
+    /// starts without the previous one's cookies, history or open tabs. Chrome is killed if it
+    /// hasn't exited 5 seconds after being asked to.
+    pub async fn reset(&self) -> Result<(), VirtualMachineError> {
+        self.execute_with(
+            "pkill google-chrome; \
+             i=0; while pgrep google-chrome > /dev/null && [ $i -lt 50 ]; do sleep 0.1; i=$((i + 1)); done; \
+             pkill -9 google-chrome; while pgrep google-chrome > /dev/null; do sleep 0.1; done; \
+             rm -rf /root/.config/google-chrome",
+            &ExecOptions::new().set_timeout(RESET_TIMEOUT),
+        ).await?;
 
         Ok(())
     }
@@ -0,0 +1,23 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turns George's progress output on or off for the whole process.
+pub(crate) fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Prints like `println!` unless logging has been turned off.
+macro_rules! log {
+    ($($arg:tt)*) => {
+        if $crate::logging::is_enabled() {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use log;
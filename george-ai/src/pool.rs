@@ -0,0 +1,282 @@
+use crate::config::GeorgeConfig;
+use crate::daemon::DaemonError;
+use crate::logging::log;
+use crate::virtual_machine::VirtualMachineError;
+use crate::George;
+use futures_util::future::try_join_all;
+use std::error::Error;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+struct IdleGeorge {
+    george: George,
+    needs_reset: bool,
+}
+
+/// A fixed set of started Georges which are handed out to concurrent automations.
+///
+/// Every session runs in its own container with its own port and network. At most `size`
+/// sessions are in use at once; [`GeorgePool::acquire`] waits for one to be returned when they
+/// all are. A session is reset (Chrome closed and its profile deleted) before it is handed out
+/// again. Combine with [`GeorgeConfig::set_max_concurrent_vision_requests`] to also cap the load
+/// on the vision model.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use george_ai::{GeorgeConfig, GeorgePool};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let config = GeorgeConfig::new("https://your-molmo-llm.com")
+///         .set_max_concurrent_vision_requests(4);
+///     let pool = GeorgePool::start(config, 8).await?;
+///
+///     {
+///         let george = pool.acquire().await?;
+///         george.open_chrome("https://some-website.com").await?;
+///         george.click("sign in link").await?;
+///     }
+///
+///     pool.stop().await?;
+///     Ok(())
+/// }
+/// ```
+pub struct GeorgePool {
+    idle: Arc<Mutex<Vec<IdleGeorge>>>,
+    permits: Arc<Semaphore>,
+    size: AtomicUsize,
+}
+
+impl GeorgePool {
+    /// Starts `size` Georges from the config concurrently, each in its own container. A session
+    /// name set in the config gets the session's index appended (`checkout-flow-0`,
+    /// `checkout-flow-1`, ...) so every session has its own named container.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The settings every session is created with.
+    /// * `size` - How many sessions to start, which is also how many can be in use at once.
+    pub async fn start(config: GeorgeConfig, size: usize) -> Result<Self, Box<dyn Error>> {
+        let starts = (0..size).map(|index| {
+            let config = session_config(&config, index);
+            async move {
+                let mut george = George::with_config(config);
+                george.start().await?;
+                Ok::<_, Box<dyn Error>>(george)
+            }
+        });
+        let georges = try_join_all(starts).await?;
+        log!("Started a pool of {} sessions", size);
+
+        Ok(Self::with_georges(georges))
+    }
+
+    /// Connects one session to each of the already running daemons, see [`George::connect`].
+    /// These sessions have no container, so they aren't reset between uses.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The settings every session is created with.
+    /// * `daemon_urls` - The URLs of the daemons, e.g. `http://192.168.1.20:3000`.
+    pub async fn connect(config: GeorgeConfig, daemon_urls: &[&str]) -> Result<Self, DaemonError> {
+        let connects = daemon_urls.iter().map(|daemon_url| async {
+            let mut george = George::with_config(config.clone());
+            george.connect(daemon_url).await?;
+            Ok::<_, DaemonError>(george)
+        });
+
+        Ok(Self::with_georges(try_join_all(connects).await?))
+    }
+
+    fn with_georges(georges: Vec<George>) -> Self {
+        let size = georges.len();
+        let idle = georges.into_iter()
+            .map(|george| IdleGeorge { george, needs_reset: false })
+            .collect();
+
+        Self {
+            idle: Arc::new(Mutex::new(idle)),
+            permits: Arc::new(Semaphore::new(size)),
+            size: AtomicUsize::new(size),
+        }
+    }
+
+    /// Waits for a session to be free and hands it out. The session returns to the pool when the
+    /// [`PooledGeorge`] is dropped.
+    ///
+    /// A session which fails to reset is torn down and the pool shrinks by one, so later calls
+    /// don't keep handing out the broken session. Once every session has been removed this fails
+    /// with [`VirtualMachineError::PoolExhausted`] instead of waiting forever.
+    pub async fn acquire(&self) -> Result<PooledGeorge, VirtualMachineError> {
+        let permit = self.permits.clone().acquire_owned().await
+            .map_err(|_| VirtualMachineError::PoolExhausted)?;
+        let idle = self.idle.lock().unwrap().pop()
+            .expect("A permit guarantees an idle session");
+
+        if idle.needs_reset && idle.george.virtual_machine.is_some() {
+            if let Err(e) = idle.george.reset().await {
+                log!("Removing a session from the pool after it failed to reset: {}", e);
+                self.remove(permit);
+                return Err(e);
+            }
+        }
+
+        Ok(PooledGeorge {
+            george: Some(idle.george),
+            idle: self.idle.clone(),
+            _permit: permit,
+        })
+    }
+
+    /// Shrinks the pool by the session holding the permit, closing it when none are left so
+    /// waiting and later calls to [`GeorgePool::acquire`] fail.
+    fn remove(&self, permit: OwnedSemaphorePermit) {
+        permit.forget();
+        if self.size.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.permits.close();
+        }
+    }
+
+    /// How many sessions are free right now.
+    pub fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Waits for every session to be returned, then stops them all.
+    pub async fn stop(self) -> Result<(), Box<dyn Error>> {
+        let size = self.size.load(Ordering::SeqCst);
+        let _permits = match size {
+            0 => None,
+            size => Some(self.permits.acquire_many(size as u32).await?),
+        };
+        let idle = std::mem::take(&mut *self.idle.lock().unwrap());
+
+        for IdleGeorge { mut george, .. } in idle {
+            george.stop().await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The config for the session at `index` of a pool, with the index appended to its session name.
+fn session_config(config: &GeorgeConfig, index: usize) -> GeorgeConfig {
+    let mut config = config.clone();
+    if let Some(session_name) = config.virtual_machine_settings.session_name.take() {
+        config.virtual_machine_settings = config.virtual_machine_settings
+            .set_session_name(format!("{}-{}", session_name, index));
+    }
+
+    config
+}
+
+/// A session handed out by [`GeorgePool::acquire`]. It dereferences to [`George`] and returns to
+/// the pool when dropped.
+pub struct PooledGeorge {
+    george: Option<George>,
+    idle: Arc<Mutex<Vec<IdleGeorge>>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledGeorge {
+    type Target = George;
+
+    fn deref(&self) -> &George {
+        self.george.as_ref().expect("A pooled George is only taken when it is dropped")
+    }
+}
+
+impl DerefMut for PooledGeorge {
+    fn deref_mut(&mut self) -> &mut George {
+        self.george.as_mut().expect("A pooled George is only taken when it is dropped")
+    }
+}
+
+impl Drop for PooledGeorge {
+    fn drop(&mut self) {
+        if let Some(george) = self.george.take() {
+            self.idle.lock().unwrap().push(IdleGeorge { george, needs_reset: true });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{FakeDaemon, FakeDaemonAction, MockVisionBackend};
+    use crate::VirtualMachineSettings;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_a_free_session() {
+        let fake_daemon = FakeDaemon::start().await.unwrap();
+        let vision = MockVisionBackend::new();
+        vision.add_point("button", 100, 100);
+        let config = GeorgeConfig::default().set_vision_backend(vision);
+        let pool = GeorgePool::connect(config, &[&fake_daemon.url()]).await.unwrap();
+
+        let george = pool.acquire().await.unwrap();
+        assert_eq!(pool.available(), 0);
+        assert!(timeout(Duration::from_millis(50), pool.acquire()).await.is_err());
+
+        george.click("button").await.unwrap();
+        drop(george);
+
+        let george = timeout(Duration::from_millis(50), pool.acquire()).await.unwrap().unwrap();
+        george.click("button").await.unwrap();
+        assert_eq!(fake_daemon.actions().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_fails_once_every_session_is_removed() {
+        let fake_daemon = FakeDaemon::start().await.unwrap();
+        let config = GeorgeConfig::default().set_vision_backend(MockVisionBackend::new());
+        let pool = GeorgePool::connect(config, &[&fake_daemon.url()]).await.unwrap();
+
+        let permit = pool.permits.clone().acquire_owned().await.unwrap();
+        pool.idle.lock().unwrap().pop();
+        pool.remove(permit);
+
+        let result = timeout(Duration::from_millis(50), pool.acquire()).await.unwrap();
+        assert!(matches!(result, Err(VirtualMachineError::PoolExhausted)));
+        pool.stop().await.unwrap();
+    }
+
+    #[test]
+    fn test_session_names_are_unique() {
+        let config = GeorgeConfig::default()
+            .set_virtual_machine_settings(VirtualMachineSettings::new().set_session_name(String::from("checkout-flow")));
+        let session_name = |index| session_config(&config, index).virtual_machine_settings.session_name;
+
+        assert_eq!(session_name(0).as_deref(), Some("checkout-flow-0"));
+        assert_eq!(session_name(1).as_deref(), Some("checkout-flow-1"));
+        assert_eq!(session_config(&GeorgeConfig::default(), 1).virtual_machine_settings.session_name, None);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_isolated() {
+        let first_daemon = FakeDaemon::start().await.unwrap();
+        let second_daemon = FakeDaemon::start().await.unwrap();
+        let config = GeorgeConfig::default().set_vision_backend(MockVisionBackend::new());
+        let pool = GeorgePool::connect(config, &[&first_daemon.url(), &second_daemon.url()]).await.unwrap();
+
+        let first = pool.acquire().await.unwrap();
+        let second = pool.acquire().await.unwrap();
+        first.press("a").await.unwrap();
+        second.press("b").await.unwrap();
+
+        let keys = |daemon: &FakeDaemon| daemon.actions().into_iter()
+            .map(|action| match action {
+                FakeDaemonAction::Key { keys, .. } => keys,
+                action => panic!("Unexpected action {:?}", action),
+            })
+            .collect::<Vec<_>>();
+        let mut pressed = [keys(&first_daemon), keys(&second_daemon)];
+        pressed.sort();
+        assert_eq!(pressed, [vec![String::from("a")], vec![String::from("b")]]);
+    }
+}
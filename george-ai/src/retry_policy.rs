@@ -0,0 +1,235 @@
+use crate::daemon::DaemonError;
+use crate::logging::log;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// How long to wait between attempts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Backoff {
+    /// Waits the same amount of time after every attempt.
+    Fixed(Duration),
+    /// Doubles the wait after every attempt, starting at `initial` and never exceeding `max`.
+    Exponential { initial: Duration, max: Duration },
+}
+
+/// Controls how George retries an action while the screen settles or the vision model misses.
+///
+/// # Example
+///
+/// ```rust
+/// use george_ai::{Backoff, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new()
+///     .set_timeout(Duration::from_secs(30))
+///     .set_backoff(Backoff::Exponential {
+///         initial: Duration::from_millis(100),
+///         max: Duration::from_secs(2),
+///     })
+///     .set_max_attempts(10);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    timeout: Duration,
+    backoff: Backoff,
+    max_attempts: Option<u32>,
+    is_retryable: fn(&DaemonError) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Retries for up to 10 seconds, waiting 10 milliseconds between attempts, whenever the vision
+    /// model's answer could not be parsed, verification samples didn't agree, or a request failed
+    /// in a way which may be transient: the connection failed, or the vision server answered with a
+    /// 5xx or 429 status.
+    ///
+    /// Only finding elements and reading the screen are retried. Clicks, typing and other actions
+    /// are sent once, so a request which failed after the daemon acted is not repeated.
+    ///
+    /// This is also the policy [`crate::George::wait_until_text_is_visible`] uses, which used to
+    /// give up after 5 seconds.
+    pub fn new() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            backoff: Backoff::Fixed(Duration::from_millis(10)),
+            max_attempts: None,
+            is_retryable: |error| match error {
+                DaemonError::FailedToParseCoordinates(_)
+                | DaemonError::FailedToParseExistence(_)
+                | DaemonError::LowConfidence { .. }
+                | DaemonError::RequestFailed(_) => true,
+                DaemonError::VisionRequestFailed { status, .. } => *status == 429 || *status >= 500,
+                _ => false,
+            },
+        }
+    }
+
+    /// Sets how long to keep retrying before giving up.
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how long to wait between attempts.
+    pub fn set_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Gives up after this many attempts, even if the timeout has not elapsed.
+    pub fn set_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Sets which errors are retried. All other errors are returned immediately.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use george_ai::{DaemonError, RetryPolicy};
+    ///
+    /// // Fail fast on transport errors and only retry unparseable answers.
+    /// let policy = RetryPolicy::new().set_retryable(|error| matches!(
+    ///     error,
+    ///     DaemonError::FailedToParseCoordinates(_) | DaemonError::FailedToParseExistence(_)
+    /// ));
+    /// ```
+    pub fn set_retryable(mut self, is_retryable: fn(&DaemonError) -> bool) -> Self {
+        self.is_retryable = is_retryable;
+        self
+    }
+
+    /// Returns how long to wait after the given attempt, starting at 1.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        match &self.backoff {
+            Backoff::Fixed(delay) => *delay,
+            Backoff::Exponential { initial, max } => {
+                let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+                initial.saturating_mul(factor).min(*max)
+            }
+        }
+    }
+
+    /// Runs `operation` until it returns a value, fails with an error which isn't retryable, or
+    /// the policy is exhausted, in which case `exhausted` is returned. If the last attempt failed
+    /// with [`DaemonError::LowConfidence`] that is returned instead, since it says more than a
+    /// timeout. The operation returns `Ok(None)` when it should be retried without an error (e.g.
+    /// text is not visible yet).
+    pub(crate) async fn retry<T, F, Fut>(&self, description: &str, exhausted: DaemonError, mut operation: F) -> Result<T, DaemonError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Option<T>, DaemonError>>,
+    {
+        let start = Instant::now();
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            let low_confidence = match operation().await {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => {
+                    log!("Failed to {}. Retrying...", description);
+                    None
+                }
+                Err(e) if (self.is_retryable)(&e) => {
+                    log!("Failed to {}: {}. Retrying...", description, e);
+                    matches!(e, DaemonError::LowConfidence { .. }).then_some(e)
+                }
+                Err(e) => return Err(e),
+            };
+
+            let elapsed = start.elapsed();
+            let out_of_attempts = self.max_attempts.is_some_and(|max_attempts| attempt >= max_attempts);
+            if out_of_attempts || elapsed >= self.timeout {
+                return Err(low_confidence.unwrap_or(exhausted));
+            }
+
+            sleep(self.delay(attempt).min(self.timeout - elapsed)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_exponential_delay() {
+        let policy = RetryPolicy::new().set_backoff(Backoff::Exponential {
+            initial: Duration::from_millis(100),
+            max: Duration::from_millis(500),
+        });
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_millis(500));
+        assert_eq!(policy.delay(40), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_after_max_attempts() {
+        let policy = RetryPolicy::new().set_max_attempts(3);
+        let attempts = Cell::new(0);
+
+        let result: Result<(), DaemonError> = policy.retry("click", DaemonError::SelectorTimeout(String::from("button")), || {
+            attempts.set(attempts.get() + 1);
+            async { Err(DaemonError::FailedToParseCoordinates(String::from("nothing"))) }
+        }).await;
+
+        assert!(matches!(result, Err(DaemonError::SelectorTimeout(_))));
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_returns_errors_which_are_not_retryable() {
+        let policy = RetryPolicy::new();
+        let attempts = Cell::new(0);
+
+        let result: Result<(), DaemonError> = policy.retry("click", DaemonError::SelectorTimeout(String::from("button")), || {
+            attempts.set(attempts.get() + 1);
+            async { Err(DaemonError::NotStarted) }
+        }).await;
+
+        assert!(matches!(result, Err(DaemonError::NotStarted)));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_vision_errors_by_default() {
+        let policy = RetryPolicy::new().set_max_attempts(2);
+        let attempts = Cell::new(0);
+
+        let result: Result<(), DaemonError> = policy.retry("click", DaemonError::SelectorTimeout(String::from("button")), || {
+            attempts.set(attempts.get() + 1);
+            let status = if attempts.get() == 1 { 503 } else { 401 };
+            async move { Err(DaemonError::VisionRequestFailed { status, body: String::new() }) }
+        }).await;
+
+        assert!(matches!(result, Err(DaemonError::VisionRequestFailed { status: 401, .. })));
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_returns_low_confidence_when_exhausted() {
+        let policy = RetryPolicy::new().set_max_attempts(2);
+        let attempts = Cell::new(0);
+
+        let result: Result<(), DaemonError> = policy.retry("click", DaemonError::SelectorTimeout(String::from("button")), || {
+            attempts.set(attempts.get() + 1);
+            async { Err(DaemonError::LowConfidence { selector: String::from("button"), confidence: 0.5 }) }
+        }).await;
+
+        assert!(matches!(result, Err(DaemonError::LowConfidence { .. })));
+        assert_eq!(attempts.get(), 2);
+    }
+}
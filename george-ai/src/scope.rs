@@ -0,0 +1,164 @@
+use crate::daemon::DaemonError;
+use crate::input::ClickOptions;
+use crate::George;
+
+/// A rectangle on the screen in pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// The point in the middle of the region.
+    pub fn center(&self) -> (u32, u32) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    /// Whether the pixel lies within the region.
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+
+    /// Returns the part of the region which lies within a `width` by `height` screen, or `None`
+    /// if they don't overlap.
+    pub(crate) fn clamp(&self, width: u32, height: u32) -> Option<Region> {
+        let right = self.x.saturating_add(self.width).min(width);
+        let bottom = self.y.saturating_add(self.height).min(height);
+
+        (self.x < right && self.y < bottom).then(|| Region::new(self.x, self.y, right - self.x, bottom - self.y))
+    }
+}
+
+/// The box on the screen covered by an element, as returned by [`George::element_box`].
+pub type ElementBox = Region;
+
+/// The part of the screen [`George::within`] searches: explicit pixels or an element, such as a
+/// form, which is located first.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Area {
+    Region(Region),
+    Selector(String),
+}
+
+impl From<Region> for Area {
+    fn from(region: Region) -> Self {
+        Area::Region(region)
+    }
+}
+
+impl From<&str> for Area {
+    fn from(selector: &str) -> Self {
+        Area::Selector(selector.to_string())
+    }
+}
+
+impl From<String> for Area {
+    fn from(selector: String) -> Self {
+        Area::Selector(selector)
+    }
+}
+
+/// Interacts with elements inside a region of the screen, handed out by [`George::within`].
+/// Coordinates are translated back to screen pixels.
+pub struct Scope<'a> {
+    george: &'a George,
+    region: Region,
+}
+
+impl<'a> Scope<'a> {
+    pub(crate) fn new(george: &'a George, region: Region) -> Self {
+        Self { george, region }
+    }
+
+    /// The region this scope searches, in screen pixels.
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Returns the screen coordinate of the element in this scope identified by the selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element (e.g., "submit button").
+    pub async fn coordinate_of(&self, selector: &str) -> Result<(u32, u32), DaemonError> {
+        self.george.retry_policy.retry(
+            &format!("locate '{}' within {:?}", selector, self.region),
+            DaemonError::SelectorTimeout(String::from(selector)),
+            || async { self.george.daemon.coordinate_of_within(selector, Some(self.region)).await.map(Some) },
+        ).await
+    }
+
+    /// Clicks on the element in this scope identified by the selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to click (e.g., "submit button").
+    pub async fn click(&self, selector: &str) -> Result<(), DaemonError> {
+        self.click_with_options(selector, &ClickOptions::new()).await
+    }
+
+    /// Clicks on the element in this scope like [`Scope::click`] with a specific button, click
+    /// count and held modifiers.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to click.
+    /// * `options` - How the element is clicked.
+    pub async fn click_with_options(&self, selector: &str, options: &ClickOptions) -> Result<(), DaemonError> {
+        let (x, y) = self.coordinate_of(selector).await?;
+        self.george.daemon.click_coordinate(x, y, options).await
+    }
+
+    /// Fills in the form field in this scope identified by the selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the form field (e.g., "input Email text field").
+    /// * `with` - The text to enter into the field.
+    pub async fn fill_in(&self, selector: &str, with: &str) -> Result<(), DaemonError> {
+        self.click(selector).await?;
+        self.george.daemon.type_text(with).await
+    }
+
+    /// Moves the mouse over the element in this scope identified by the selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - A natural language description of the element to hover over.
+    pub async fn hover(&self, selector: &str) -> Result<(), DaemonError> {
+        let (x, y) = self.coordinate_of(selector).await?;
+        self.george.daemon.move_mouse(x, y).await
+    }
+
+    /// Checks whether the text is visible in this scope.
+    pub async fn is_text_visible(&self, text: &str) -> Result<bool, DaemonError> {
+        self.george.daemon.is_text_visible_within(text, Some(self.region)).await
+    }
+
+    /// Asks the vision backend a free-form question about this scope.
+    pub async fn ask(&self, prompt: &str) -> Result<String, DaemonError> {
+        self.george.daemon.ask_within(prompt, Some(self.region)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp() {
+        let region = Region::new(900, 700, 300, 300);
+
+        assert_eq!(region.clamp(1024, 768), Some(Region::new(900, 700, 124, 68)));
+        assert_eq!(Region::new(10, 10, 20, 20).clamp(1024, 768), Some(Region::new(10, 10, 20, 20)));
+        assert_eq!(Region::new(1100, 10, 20, 20).clamp(1024, 768), None);
+        assert_eq!(Region::new(10, 10, 0, 20).clamp(1024, 768), None);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::scope::Region;
+use image::{Rgb, RgbImage};
+use std::collections::{HashMap, VecDeque};
+
+/// The largest per channel difference (0-255) between two colors considered the same.
+const COLOR_TOLERANCE: u8 = 16;
+/// Half the size of the square around the located point used to pick the element's color.
+const SEED_RADIUS: u32 = 3;
+/// How many pixels of background may separate the parts of an element without a fill, such as
+/// the letters of a link.
+const GAP: u32 = 4;
+
+/// Finds the box of the element under the point in the image.
+///
+/// The background is the color around the area of the most common color near the point, or that
+/// color itself when it reaches the edges of the image. The element is then everything nearby
+/// which differs from the background, so the label of a button and the letters of a link are
+/// part of one element.
+pub(crate) fn segment(image: &RgbImage, x: u32, y: u32) -> Option<Region> {
+    let (width, height) = image.dimensions();
+    if x >= width || y >= height {
+        return None;
+    }
+
+    let seed = seed_color(image, x, y);
+    let seeds = window(x, y, width, height)
+        .filter(|&(x, y)| similar(image.get_pixel(x, y), &seed));
+    let area = flood(image, seeds, 1, |pixel| similar(pixel, &seed));
+
+    let touches_edges = area.x == 0 || area.y == 0 || area.x + area.width == width || area.y + area.height == height;
+    let background = if touches_edges { seed } else { border_color(image, area) };
+
+    let foreground = window(x, y, width, height)
+        .filter(|&(x, y)| !similar(image.get_pixel(x, y), &background))
+        .collect::<Vec<_>>();
+    if foreground.is_empty() {
+        return None;
+    }
+
+    Some(flood(image, foreground.into_iter(), GAP + 1, |pixel| !similar(pixel, &background)))
+}
+
+fn seed_color(image: &RgbImage, x: u32, y: u32) -> Rgb<u8> {
+    let (width, height) = image.dimensions();
+    let mut counts = HashMap::new();
+    for (x, y) in window(x, y, width, height) {
+        *counts.entry(image.get_pixel(x, y).0).or_insert(0) += 1;
+    }
+
+    let (color, _) = counts.into_iter()
+        .max_by_key(|&(color, count)| (count, color))
+        .expect("The window contains the point itself");
+    Rgb(color)
+}
+
+/// The most common color of the pixels just outside the region.
+fn border_color(image: &RgbImage, region: Region) -> Rgb<u8> {
+    let (width, height) = image.dimensions();
+    let (left, top) = (region.x.saturating_sub(1), region.y.saturating_sub(1));
+    let (right, bottom) = ((region.x + region.width).min(width - 1), (region.y + region.height).min(height - 1));
+
+    let mut counts = HashMap::new();
+    for y in top..=bottom {
+        for x in left..=right {
+            let on_border = x == left || x == right || y == top || y == bottom;
+            if on_border && !region.contains(x, y) {
+                *counts.entry(image.get_pixel(x, y).0).or_insert(0) += 1;
+            }
+        }
+    }
+
+    counts.into_iter()
+        .max_by_key(|&(color, count)| (count, color))
+        .map_or(*image.get_pixel(region.x, region.y), |(color, _)| Rgb(color))
+}
+
+/// The pixels in the square of [`SEED_RADIUS`] around the point which are in the image.
+fn window(x: u32, y: u32, width: u32, height: u32) -> impl Iterator<Item = (u32, u32)> {
+    let columns = x.saturating_sub(SEED_RADIUS)..(x + SEED_RADIUS + 1).min(width);
+    let rows = y.saturating_sub(SEED_RADIUS)..(y + SEED_RADIUS + 1).min(height);
+
+    rows.flat_map(move |y| columns.clone().map(move |x| (x, y)))
+}
+
+fn similar(pixel: &Rgb<u8>, color: &Rgb<u8>) -> bool {
+    pixel.0.iter().zip(color.0.iter()).all(|(a, b)| a.abs_diff(*b) <= COLOR_TOLERANCE)
+}
+
+/// Returns the bounding box of the pixels reachable from the seeds through pixels accepted by
+/// `include`, stepping at most `reach` pixels at a time.
+fn flood(
+    image: &RgbImage,
+    seeds: impl Iterator<Item = (u32, u32)>,
+    reach: u32,
+    include: impl Fn(&Rgb<u8>) -> bool,
+) -> Region {
+    let (width, height) = image.dimensions();
+    let mut visited = vec![false; (width * height) as usize];
+    let mut queue = VecDeque::new();
+    for (x, y) in seeds {
+        visited[(y * width + x) as usize] = true;
+        queue.push_back((x, y));
+    }
+
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    while let Some((x, y)) = queue.pop_front() {
+        (left, top, right, bottom) = (left.min(x), top.min(y), right.max(x), bottom.max(y));
+
+        for ny in y.saturating_sub(reach)..(y + reach + 1).min(height) {
+            for nx in x.saturating_sub(reach)..(x + reach + 1).min(width) {
+                // A reach of 1 only steps to the four direct neighbors.
+                if reach == 1 && nx != x && ny != y {
+                    continue;
+                }
+                let index = (ny * width + nx) as usize;
+                if !visited[index] && include(image.get_pixel(nx, ny)) {
+                    visited[index] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+
+    Region::new(left, top, right - left + 1, bottom - top + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+    const BLUE: Rgb<u8> = Rgb([30, 90, 220]);
+    const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+
+    fn fill(image: &mut RgbImage, region: Region, color: Rgb<u8>) {
+        for y in region.y..region.y + region.height {
+            for x in region.x..region.x + region.width {
+                image.put_pixel(x, y, color);
+            }
+        }
+    }
+
+    #[test]
+    fn test_segment_button_with_label() {
+        let mut image = RgbImage::from_pixel(400, 200, WHITE);
+        fill(&mut image, Region::new(100, 50, 120, 40), BLUE);
+        // The label, with the located point on one of its letters.
+        fill(&mut image, Region::new(140, 65, 3, 10), WHITE);
+        fill(&mut image, Region::new(150, 65, 3, 10), WHITE);
+
+        assert_eq!(segment(&image, 141, 70), Some(Region::new(100, 50, 120, 40)));
+    }
+
+    #[test]
+    fn test_segment_text_without_background() {
+        let mut image = RgbImage::from_pixel(400, 200, WHITE);
+        for left in [200, 208, 216, 224] {
+            fill(&mut image, Region::new(left, 100, 5, 12), BLACK);
+        }
+
+        assert_eq!(segment(&image, 212, 106), Some(Region::new(200, 100, 29, 12)));
+    }
+
+    #[test]
+    fn test_segment_empty_background() {
+        let image = RgbImage::from_pixel(400, 200, WHITE);
+
+        assert_eq!(segment(&image, 200, 100), None);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::input::{ClickOptions, KeyDirection, MouseButton, ScrollAxis};
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use image::{ImageFormat, Rgba, RgbaImage};
+use serde::Deserialize;
+use serde_json::json;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// An action the fake daemon received.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FakeDaemonAction {
+    Click { x: i32, y: i32, options: ClickOptions },
+    Type { text: String },
+    Key { keys: String, direction: KeyDirection },
+    Scroll { amount: i32, axis: ScrollAxis, at: Option<(i32, i32)> },
+    MouseMove { x: i32, y: i32 },
+    MouseDown { button: MouseButton },
+    MouseUp { button: MouseButton },
+    Drag { from: (i32, i32), to: (i32, i32), button: MouseButton, steps: u32 },
+}
+
+struct FakeDaemonState {
+    screenshot: Vec<u8>,
+    actions: Vec<FakeDaemonAction>,
+    mouse: (i32, i32),
+}
+
+type SharedState = Arc<Mutex<FakeDaemonState>>;
+
+/// An in-process stand-in for the george-daemon HTTP API.
+///
+/// It serves a configurable screenshot and records the actions it receives instead of driving
+/// a real mouse and keyboard, so automation flows can be tested without Docker. The server is
+/// shut down when the fake daemon is dropped.
+pub struct FakeDaemon {
+    port: u16,
+    state: SharedState,
+    server: JoinHandle<()>,
+}
+
+impl FakeDaemon {
+    /// Starts the fake daemon on a free local port with a blank 1024x768 screen.
+    pub async fn start() -> Result<Self, std::io::Error> {
+        Self::with_display_size(1024, 768).await
+    }
+
+    /// Starts the fake daemon on a free local port with a blank screen of the given size.
+    pub async fn with_display_size(width: u32, height: u32) -> Result<Self, std::io::Error> {
+        let state = Arc::new(Mutex::new(FakeDaemonState {
+            screenshot: blank_screenshot(width, height),
+            actions: Vec::new(),
+            mouse: (0, 0),
+        }));
+
+        let app = Router::new()
+            .route("/screenshot", get(screenshot_handler))
+            .route("/click", post(click_handler))
+            .route("/type", post(type_handler))
+            .route("/key", post(key_handler))
+            .route("/scroll", post(scroll_handler))
+            .route("/display", get(display_handler))
+            .route("/mouse", get(position_handler))
+            .route("/mouse/move", post(move_handler))
+            .route("/mouse/down", post(down_handler))
+            .route("/mouse/up", post(up_handler))
+            .route("/drag", post(drag_handler))
+            .route("/healthz", get(|| async { StatusCode::OK }))
+            .with_state(state.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        Ok(Self { port, state, server })
+    }
+
+    /// The port the fake daemon is listening on, suitable for `Daemon::set_port`.
+    pub fn port(&self) -> String {
+        self.port.to_string()
+    }
+
+    /// The URL the fake daemon is listening on, suitable for `George::connect`.
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// Replaces the PNG returned by `/screenshot`.
+    pub fn set_screenshot(&self, png: &[u8]) {
+        self.state.lock().unwrap().screenshot = png.to_vec();
+    }
+
+    /// Returns every action received so far, in order.
+    pub fn actions(&self) -> Vec<FakeDaemonAction> {
+        self.state.lock().unwrap().actions.clone()
+    }
+
+    fn record(state: &SharedState, action: FakeDaemonAction) {
+        let mut state = state.lock().unwrap();
+        match &action {
+            FakeDaemonAction::Click { x, y, .. } | FakeDaemonAction::MouseMove { x, y } => state.mouse = (*x, *y),
+            FakeDaemonAction::Scroll { at: Some(at), .. } | FakeDaemonAction::Drag { to: at, .. } => state.mouse = *at,
+            _ => {}
+        }
+        state.actions.push(action);
+    }
+}
+
+impl Drop for FakeDaemon {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn blank_screenshot(width: u32, height: u32) -> Vec<u8> {
+    let image = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+    let mut buffer = Vec::new();
+    image.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png)
+        .expect("Failed to encode image to PNG");
+    buffer
+}
+
+#[derive(Deserialize)]
+struct ClickPayload {
+    x: i32,
+    y: i32,
+    #[serde(flatten)]
+    options: ClickOptions,
+}
+
+#[derive(Deserialize)]
+struct TypePayload {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize)]
+struct ButtonPayload {
+    #[serde(default)]
+    button: MouseButton,
+}
+
+#[derive(Deserialize)]
+struct DragPayload {
+    from: Point,
+    to: Point,
+    #[serde(default)]
+    button: MouseButton,
+    steps: u32,
+}
+
+#[derive(Deserialize)]
+struct ScrollPayload {
+    amount: i32,
+    #[serde(default)]
+    axis: ScrollAxis,
+    x: Option<i32>,
+    y: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct KeyPayload {
+    keys: String,
+    #[serde(default)]
+    direction: KeyDirection,
+}
+
+async fn screenshot_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let screenshot = state.lock().unwrap().screenshot.clone();
+
+    ([(header::CONTENT_TYPE, "image/png")], screenshot)
+}
+
+async fn click_handler(State(state): State<SharedState>, Json(payload): Json<ClickPayload>) -> impl IntoResponse {
+    FakeDaemon::record(&state, FakeDaemonAction::Click { x: payload.x, y: payload.y, options: payload.options });
+
+    Json(json!({
+        "status": "clicked",
+        "x": payload.x,
+        "y": payload.y
+    }))
+}
+
+async fn type_handler(State(state): State<SharedState>, Json(payload): Json<TypePayload>) -> impl IntoResponse {
+    FakeDaemon::record(&state, FakeDaemonAction::Type { text: payload.text.clone() });
+
+    Json(json!({
+        "status": "typed",
+        "text": payload.text
+    }))
+}
+
+async fn key_handler(State(state): State<SharedState>, Json(payload): Json<KeyPayload>) -> impl IntoResponse {
+    FakeDaemon::record(&state, FakeDaemonAction::Key { keys: payload.keys.clone(), direction: payload.direction });
+
+    Json(json!({
+        "status": "pressed",
+        "keys": payload.keys,
+        "direction": payload.direction
+    }))
+}
+
+async fn scroll_handler(State(state): State<SharedState>, Json(payload): Json<ScrollPayload>) -> impl IntoResponse {
+    let at = payload.x.zip(payload.y);
+    FakeDaemon::record(&state, FakeDaemonAction::Scroll { amount: payload.amount, axis: payload.axis, at });
+
+    Json(json!({
+        "status": "scrolled",
+        "amount": payload.amount,
+        "axis": payload.axis,
+        "x": payload.x,
+        "y": payload.y
+    }))
+}
+
+async fn display_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let screenshot = state.lock().unwrap().screenshot.clone();
+
+    match image::load_from_memory(&screenshot) {
+        Ok(image) => Ok(Json(json!({
+            "width": image.width(),
+            "height": image.height()
+        }))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+async fn position_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let (x, y) = state.lock().unwrap().mouse;
+
+    Json(json!({
+        "x": x,
+        "y": y
+    }))
+}
+
+async fn move_handler(State(state): State<SharedState>, Json(payload): Json<Point>) -> impl IntoResponse {
+    FakeDaemon::record(&state, FakeDaemonAction::MouseMove { x: payload.x, y: payload.y });
+
+    Json(json!({
+        "status": "moved",
+        "x": payload.x,
+        "y": payload.y
+    }))
+}
+
+async fn down_handler(State(state): State<SharedState>, Json(payload): Json<ButtonPayload>) -> impl IntoResponse {
+    FakeDaemon::record(&state, FakeDaemonAction::MouseDown { button: payload.button });
+
+    Json(json!({
+        "status": "pressed",
+        "button": payload.button
+    }))
+}
+
+async fn up_handler(State(state): State<SharedState>, Json(payload): Json<ButtonPayload>) -> impl IntoResponse {
+    FakeDaemon::record(&state, FakeDaemonAction::MouseUp { button: payload.button });
+
+    Json(json!({
+        "status": "released",
+        "button": payload.button
+    }))
+}
+
+async fn drag_handler(State(state): State<SharedState>, Json(payload): Json<DragPayload>) -> impl IntoResponse {
+    FakeDaemon::record(&state, FakeDaemonAction::Drag {
+        from: (payload.from.x, payload.from.y),
+        to: (payload.to.x, payload.to.y),
+        button: payload.button,
+        steps: payload.steps,
+    });
+
+    Json(json!({
+        "status": "dragged",
+        "steps": payload.steps
+    }))
+}
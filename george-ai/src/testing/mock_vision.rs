@@ -0,0 +1,254 @@
+use crate::daemon::DaemonError;
+use crate::vision::VisionBackend;
+use async_trait::async_trait;
+use bytes::Bytes;
+use image::{GrayImage, ImageReader};
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+/// The largest average per pixel difference (0-255) accepted as a template match.
+const TEMPLATE_TOLERANCE: u64 = 8;
+
+enum Locator {
+    Point(u32, u32),
+    Template(GrayImage),
+    Box(u32, u32, u32, u32),
+}
+
+#[derive(Default)]
+struct MockState {
+    locators: Vec<(String, Locator)>,
+    visible_text: Vec<String>,
+    answers: Vec<(String, String)>,
+    prompts: Vec<String>,
+}
+
+/// A deterministic vision backend which answers from scripted rules instead of a model.
+///
+/// Rules are matched when the prompt contains their selector and the longest matching selector
+/// wins, so "blue submit button" can be scripted separately from "submit button". Clones share
+/// the same rules, which makes it possible to change what is "on the screen" after handing the
+/// backend to George.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use george_ai::George;
+/// use george_ai::testing::{FakeDaemon, MockVisionBackend};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let fake_daemon = FakeDaemon::start().await?;
+///     let vision = MockVisionBackend::new();
+///     vision.add_point("sign in button", 512, 300);
+///
+///     let mut george = George::with_vision_backend(vision.clone());
+///     george.connect(&fake_daemon.url()).await?;
+///     george.click("sign in button").await?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockVisionBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockVisionBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the selector to a fixed point in pixels of the image the backend is shown, which
+    /// is the screenshot or, inside [`crate::George::within`], the crop of its region. Adding
+    /// several points for the same selector puts several matching elements on the screen.
+    pub fn add_point(&self, selector: &str, x: u32, y: u32) {
+        self.state.lock().unwrap().locators.push((selector.to_string(), Locator::Point(x, y)));
+    }
+
+    /// Resolves the selector to a box in pixels of the image the backend is shown, like
+    /// [`MockVisionBackend::add_point`]. Prompts asking for its top left or bottom right corner are
+    /// answered with that corner, any other prompt with its center.
+    pub fn add_box(&self, selector: &str, x: u32, y: u32, width: u32, height: u32) {
+        self.state.lock().unwrap().locators.push((selector.to_string(), Locator::Box(x, y, width, height)));
+    }
+
+    /// Resolves the selector by searching the screenshot for the reference crop and returning its
+    /// center.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The selector this rule answers for.
+    /// * `reference` - An encoded image (e.g. PNG) cropped from a screenshot of the element.
+    pub fn add_template(&self, selector: &str, reference: &[u8]) -> Result<(), DaemonError> {
+        let template = image::load_from_memory(reference)?.to_luma8();
+        self.state.lock().unwrap().locators.push((selector.to_string(), Locator::Template(template)));
+
+        Ok(())
+    }
+
+    /// Sets the text reported as visible on the screen.
+    pub fn set_visible_text(&self, visible_text: &[&str]) {
+        self.state.lock().unwrap().visible_text = visible_text.iter().map(|t| t.to_string()).collect();
+    }
+
+    /// Answers free-form questions containing `question` with `answer`.
+    pub fn add_answer(&self, question: &str, answer: &str) {
+        self.state.lock().unwrap().answers.push((question.to_string(), answer.to_string()));
+    }
+
+    /// Returns every prompt the backend has received, in order.
+    pub fn prompts(&self) -> Vec<String> {
+        self.state.lock().unwrap().prompts.clone()
+    }
+
+    fn record(&self, prompt: &str) {
+        self.state.lock().unwrap().prompts.push(prompt.to_string());
+    }
+
+    /// Resolves every rule for the longest selector contained in the prompt, in the order they
+    /// were added, to points in image pixels.
+    fn points(&self, image: &Bytes, prompt: &str) -> Result<Vec<(u32, u32)>, DaemonError> {
+        let state = self.state.lock().unwrap();
+        let Some(longest) = state.locators.iter()
+            .map(|(selector, _)| selector)
+            .filter(|selector| prompt.contains(selector.as_str()))
+            .max_by_key(|selector| selector.len()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut points = Vec::new();
+        for (_, locator) in state.locators.iter().filter(|(selector, _)| selector == longest) {
+            let point = match locator {
+                Locator::Point(x, y) => Some((*x, *y)),
+                Locator::Template(template) => find_template(&image::load_from_memory(image)?.to_luma8(), template),
+                Locator::Box(x, y, box_width, box_height) => Some(if prompt.contains("top left corner") {
+                    (*x, *y)
+                } else if prompt.contains("bottom right corner") {
+                    (x + box_width, y + box_height)
+                } else {
+                    (x + box_width / 2, y + box_height / 2)
+                }),
+            };
+            points.extend(point);
+        }
+
+        Ok(points)
+    }
+}
+
+#[async_trait]
+impl VisionBackend for MockVisionBackend {
+    async fn locate(&self, image: &Bytes, prompt: &str) -> Result<(f64, f64), DaemonError> {
+        self.record(prompt);
+        let (width, height) = dimensions(image)?;
+
+        let points = self.points(image, prompt)?;
+
+        match points.first() {
+            Some((x, y)) => Ok((*x as f64 / width * 100.0, *y as f64 / height * 100.0)),
+            None => Err(DaemonError::FailedToParseCoordinates(format!("No mock point for prompt: {}", prompt))),
+        }
+    }
+
+    async fn locate_all(&self, image: &Bytes, prompt: &str) -> Result<Vec<(f64, f64)>, DaemonError> {
+        self.record(prompt);
+        let (width, height) = dimensions(image)?;
+
+        Ok(self.points(image, prompt)?.into_iter()
+            .map(|(x, y)| (x as f64 / width * 100.0, y as f64 / height * 100.0))
+            .collect())
+    }
+
+    async fn visible_text(&self, _image: &Bytes, prompt: &str) -> Result<Vec<String>, DaemonError> {
+        self.record(prompt);
+
+        Ok(self.state.lock().unwrap().visible_text.clone())
+    }
+
+    async fn ask(&self, _image: &Bytes, prompt: &str) -> Result<String, DaemonError> {
+        self.record(prompt);
+
+        self.state.lock().unwrap().answers.iter()
+            .filter(|(question, _)| prompt.contains(question.as_str()))
+            .max_by_key(|(question, _)| question.len())
+            .map(|(_, answer)| answer.clone())
+            .ok_or_else(|| DaemonError::Unexpected(format!("No mock answer for prompt: {}", prompt)))
+    }
+}
+
+fn dimensions(image: &Bytes) -> Result<(f64, f64), DaemonError> {
+    let (width, height) = ImageReader::new(Cursor::new(image))
+        .with_guessed_format()
+        .map_err(image::ImageError::IoError)?
+        .into_dimensions()?;
+
+    Ok((width as f64, height as f64))
+}
+
+/// Finds the best match of `template` within `screen` and returns its center. Candidate positions
+/// are abandoned as soon as they can no longer beat the best match found so far.
+pub(crate) fn find_template(screen: &GrayImage, template: &GrayImage) -> Option<(u32, u32)> {
+    let (template_width, template_height) = template.dimensions();
+    if template_width == 0 || template_height == 0
+        || template_width > screen.width() || template_height > screen.height() {
+        return None;
+    }
+
+    let limit = TEMPLATE_TOLERANCE * template_width as u64 * template_height as u64;
+    let mut best: Option<(u64, u32, u32)> = None;
+
+    for top in 0..=(screen.height() - template_height) {
+        for left in 0..=(screen.width() - template_width) {
+            let budget = best.map_or(limit, |(difference, _, _)| difference);
+            let mut difference = 0u64;
+
+            'rows: for y in 0..template_height {
+                for x in 0..template_width {
+                    let expected = template.get_pixel(x, y).0[0];
+                    let actual = screen.get_pixel(left + x, top + y).0[0];
+                    difference += expected.abs_diff(actual) as u64;
+                    if difference > budget {
+                        break 'rows;
+                    }
+                }
+            }
+
+            if difference <= budget {
+                best = Some((difference, left, top));
+                if difference == 0 {
+                    return Some((left + template_width / 2, top + template_height / 2));
+                }
+            }
+        }
+    }
+
+    best.map(|(_, left, top)| (left + template_width / 2, top + template_height / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    #[test]
+    fn test_find_template() {
+        let mut screen = GrayImage::from_pixel(200, 100, Luma([255]));
+        for x in 120..160 {
+            for y in 40..60 {
+                screen.put_pixel(x, y, Luma([((x + y) % 200) as u8]));
+            }
+        }
+        let template = image::imageops::crop_imm(&screen, 120, 40, 40, 20).to_image();
+
+        assert_eq!(find_template(&screen, &template), Some((140, 50)));
+    }
+
+    #[test]
+    fn test_find_template_without_match() {
+        let screen = GrayImage::from_pixel(200, 100, Luma([255]));
+        let template = GrayImage::from_pixel(20, 20, Luma([0]));
+
+        assert_eq!(find_template(&screen, &template), None);
+    }
+}
@@ -0,0 +1,394 @@
+//! Helpers for testing automation built on George without Docker, a GPU or a network.
+//!
+//! [`MockVisionBackend`] answers vision queries from scripted rules and [`FakeDaemon`] serves the
+//! george-daemon HTTP API in-process, recording the actions it receives.
+//!
+//! These are available with the `testing` feature.
+mod fake_daemon;
+mod mock_vision;
+
+pub use fake_daemon::{FakeDaemon, FakeDaemonAction};
+pub use mock_vision::MockVisionBackend;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoxMode, ClickOptions, DaemonError, DaemonSettings, DragOptions, ElementBox, George, GeorgeConfig, KeyDirection, Modifier, MouseButton, Region, RetryPolicy, ScrollAxis, SelectOptions, Verification};
+    use image::{GrayImage, ImageFormat, Luma, Rgb, RgbImage};
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    async fn george_with_fakes() -> (George, FakeDaemon, MockVisionBackend) {
+        let fake_daemon = FakeDaemon::start().await.unwrap();
+        let vision = MockVisionBackend::new();
+        let mut george = George::with_vision_backend(vision.clone());
+        george.connect(&fake_daemon.url()).await.unwrap();
+
+        (george, fake_daemon, vision)
+    }
+
+    async fn george_with_settings(daemon_settings: DaemonSettings) -> (George, FakeDaemon, MockVisionBackend) {
+        let fake_daemon = FakeDaemon::start().await.unwrap();
+        let vision = MockVisionBackend::new();
+        let config = GeorgeConfig::default()
+            .set_daemon_settings(daemon_settings)
+            .set_vision_backend(vision.clone());
+        let mut george = George::with_config(config);
+        george.connect(&fake_daemon.url()).await.unwrap();
+
+        (george, fake_daemon, vision)
+    }
+
+    #[tokio::test]
+    async fn test_fill_in_clicks_and_types() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("input Email text field", 256, 192);
+
+        george.fill_in("input Email text field", "ada@email.com").await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![
+            FakeDaemonAction::Click { x: 256, y: 192, options: ClickOptions::new() },
+            FakeDaemonAction::Type { text: String::from("ada@email.com") },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_click_uses_the_most_specific_selector() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("submit button", 10, 10);
+        vision.add_point("blue submit button", 512, 384);
+
+        george.click("blue submit button").await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![FakeDaemonAction::Click { x: 512, y: 384, options: ClickOptions::new() }]);
+    }
+
+    #[tokio::test]
+    async fn test_click_variants() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("table row", 100, 200);
+
+        george.double_click("table row").await.unwrap();
+        george.right_click("table row").await.unwrap();
+        george.click_with_modifiers("table row", &[Modifier::Control, Modifier::Shift]).await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![
+            FakeDaemonAction::Click { x: 100, y: 200, options: ClickOptions::new().set_count(2) },
+            FakeDaemonAction::Click { x: 100, y: 200, options: ClickOptions::new().set_button(MouseButton::Right) },
+            FakeDaemonAction::Click {
+                x: 100,
+                y: 200,
+                options: ClickOptions::new().add_modifier(Modifier::Control).add_modifier(Modifier::Shift),
+            },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_click_nth_in_reading_order() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("delete button", 900, 300);
+        vision.add_point("delete button", 900, 100);
+        vision.add_point("delete button", 900, 200);
+
+        assert_eq!(george.count("delete button").await.unwrap(), 3);
+        assert_eq!(george.count("edit button").await.unwrap(), 0);
+        george.click_nth("delete button", 2).await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![FakeDaemonAction::Click { x: 900, y: 300, options: ClickOptions::new() }]);
+    }
+
+    #[tokio::test]
+    async fn test_click_nth_times_out_without_enough_matches() {
+        let (mut george, fake_daemon, vision) = george_with_fakes().await;
+        george.retry_policy = RetryPolicy::new().set_timeout(Duration::from_millis(50));
+        vision.add_point("delete button", 900, 100);
+
+        let result = george.click_nth("delete button", 1).await;
+
+        assert!(matches!(result, Err(DaemonError::SelectorTimeout(_))));
+        assert!(fake_daemon.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_keys() {
+        let (george, fake_daemon, _vision) = george_with_fakes().await;
+
+        george.press("ctrl+a").await.unwrap();
+        george.key_down("shift").await.unwrap();
+        george.key_up("shift").await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![
+            FakeDaemonAction::Key { keys: String::from("ctrl+a"), direction: KeyDirection::Click },
+            FakeDaemonAction::Key { keys: String::from("shift"), direction: KeyDirection::Down },
+            FakeDaemonAction::Key { keys: String::from("shift"), direction: KeyDirection::Up },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_scroll_until_visible() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("results list", 300, 400);
+
+        george.scroll_at("results list", 3).await.unwrap();
+        let result = george.scroll_until_visible("Submit", 2).await;
+
+        assert!(matches!(result, Err(DaemonError::NotVisibleAfterScrolling(_))));
+        assert_eq!(fake_daemon.actions(), vec![
+            FakeDaemonAction::Scroll { amount: 3, axis: ScrollAxis::Vertical, at: Some((300, 400)) },
+            FakeDaemonAction::Scroll { amount: 5, axis: ScrollAxis::Vertical, at: None },
+            FakeDaemonAction::Scroll { amount: 5, axis: ScrollAxis::Vertical, at: None },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_drag_and_drop() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("card titled Bug", 100, 200);
+        vision.add_point("Done column", 700, 200);
+
+        george.drag_and_drop("card titled Bug", "Done column").await.unwrap();
+        george.drag_and_drop_with_options("card titled Bug", "Done column", &DragOptions::new().set_steps(30)).await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![
+            FakeDaemonAction::Drag { from: (100, 200), to: (700, 200), button: MouseButton::Left, steps: 10 },
+            FakeDaemonAction::Drag { from: (100, 200), to: (700, 200), button: MouseButton::Left, steps: 30 },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_hover_moves_without_clicking() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("help icon", 900, 40);
+
+        george.hover("help icon").await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![FakeDaemonAction::MouseMove { x: 900, y: 40 }]);
+        assert_eq!(george.mouse_position().await.unwrap(), (900, 40));
+    }
+
+    #[tokio::test]
+    async fn test_select() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_box("Country dropdown", 350, 285, 100, 30);
+        vision.add_point("Canada option", 400, 360);
+        vision.set_visible_text(&["Country", "Canada", "Mexico"]);
+
+        george.select_with_options("Country dropdown", "Canada", &SelectOptions::new().set_verify(true)).await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![
+            FakeDaemonAction::Click { x: 400, y: 300, options: ClickOptions::new() },
+            FakeDaemonAction::Click { x: 400, y: 360, options: ClickOptions::new() },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_select_scrolls_the_open_list() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("Country dropdown", 400, 300);
+        vision.add_point("open dropdown list", 400, 450);
+        let options = SelectOptions::new()
+            .set_render_timeout(Duration::from_millis(20))
+            .set_max_scrolls(2);
+
+        let result = george.select_with_options("Country dropdown", "Zimbabwe", &options).await;
+
+        assert!(matches!(result, Err(DaemonError::NotVisibleAfterScrolling(_))));
+        assert_eq!(fake_daemon.actions(), vec![
+            FakeDaemonAction::Click { x: 400, y: 300, options: ClickOptions::new() },
+            FakeDaemonAction::Scroll { amount: 3, axis: ScrollAxis::Vertical, at: Some((400, 450)) },
+            FakeDaemonAction::Scroll { amount: 3, axis: ScrollAxis::Vertical, at: Some((400, 450)) },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_display_size() {
+        let fake_daemon = FakeDaemon::with_display_size(390, 844).await.unwrap();
+        let mut george = George::new("https://doesnotmatter.com");
+        george.connect(&fake_daemon.url()).await.unwrap();
+
+        assert_eq!(george.display_size().await.unwrap(), (390, 844));
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_text_is_visible() {
+        let (george, _fake_daemon, vision) = george_with_fakes().await;
+        vision.set_visible_text(&["End-to-End Test"]);
+
+        george.wait_until_text_is_visible("end-to-end test").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_text_is_visible_times_out() {
+        let (george, _fake_daemon, vision) = george_with_fakes().await;
+        vision.set_visible_text(&["End-to-End Test"]);
+        let retry_policy = RetryPolicy::new().set_timeout(Duration::from_millis(50));
+
+        let result = george.wait_until_text_is_visible_with("Submit", &retry_policy).await;
+
+        assert!(matches!(result, Err(DaemonError::TextNotVisible(text)) if text == "Submit"));
+    }
+
+    #[tokio::test]
+    async fn test_click_times_out_with_retry_policy() {
+        let (george, fake_daemon, _vision) = george_with_fakes().await;
+        let retry_policy = RetryPolicy::new().set_timeout(Duration::from_millis(50));
+
+        let result = george.click_with("missing button", &retry_policy).await;
+
+        assert!(matches!(result, Err(DaemonError::SelectorTimeout(_))));
+        assert!(fake_daemon.actions().is_empty());
+    }
+
+    /// A 400x200 screen with the same patch at (40, 40) and (240, 40), and a PNG crop of the patch.
+    fn screen_with_two_patches() -> (Vec<u8>, Vec<u8>) {
+        let mut screen = GrayImage::from_pixel(400, 200, Luma([255]));
+        for left in [40, 240] {
+            for x in 0..40 {
+                for y in 0..20 {
+                    screen.put_pixel(left + x, 40 + y, Luma([((x * 7 + y * 3) % 200) as u8]));
+                }
+            }
+        }
+        let patch = image::imageops::crop_imm(&screen, 40, 40, 40, 20).to_image();
+
+        let encode = |image: &GrayImage| {
+            let mut buffer = Vec::new();
+            image.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png).unwrap();
+            buffer
+        };
+        (encode(&screen), encode(&patch))
+    }
+
+    #[tokio::test]
+    async fn test_within_region_offsets_coordinates() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        let (screen, patch) = screen_with_two_patches();
+        fake_daemon.set_screenshot(&screen);
+        vision.add_template("submit button", &patch).unwrap();
+
+        george.click("submit button").await.unwrap();
+        george.within(Region::new(200, 0, 200, 200), |scope| async move {
+            scope.click("submit button").await
+        }).await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![
+            FakeDaemonAction::Click { x: 60, y: 50, options: ClickOptions::new() },
+            FakeDaemonAction::Click { x: 260, y: 50, options: ClickOptions::new() },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_within_selector_resolves_the_region_first() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        let (screen, patch) = screen_with_two_patches();
+        fake_daemon.set_screenshot(&screen);
+        vision.add_template("submit button", &patch).unwrap();
+        vision.add_box("billing form", 200, 20, 120, 80);
+
+        let region = george.within("billing form", |scope| async move {
+            scope.click("submit button").await?;
+            Ok(scope.region())
+        }).await.unwrap();
+
+        assert_eq!(region, Region::new(200, 20, 120, 80));
+        assert_eq!(fake_daemon.actions(), vec![FakeDaemonAction::Click { x: 260, y: 50, options: ClickOptions::new() }]);
+    }
+
+    #[tokio::test]
+    async fn test_within_translates_points_from_the_crop() {
+        let (george, fake_daemon, vision) = george_with_fakes().await;
+        vision.add_point("submit button", 60, 30);
+
+        george.within(Region::new(200, 20, 120, 80), |scope| async move {
+            scope.click("submit button").await
+        }).await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![FakeDaemonAction::Click { x: 260, y: 50, options: ClickOptions::new() }]);
+    }
+
+    #[tokio::test]
+    async fn test_within_region_outside_the_screen() {
+        let (george, _fake_daemon, _vision) = george_with_fakes().await;
+
+        let result = george.within(Region::new(5000, 0, 10, 10), |scope| async move {
+            scope.ask("what is this?").await
+        }).await;
+
+        assert!(matches!(result, Err(DaemonError::RegionOutOfBounds(_))));
+    }
+
+    #[tokio::test]
+    async fn test_element_box_from_corners() {
+        let (george, _fake_daemon, vision) = george_with_fakes().await;
+        vision.add_box("search input", 256, 48, 512, 48);
+
+        assert_eq!(george.element_box("search input").await.unwrap(), ElementBox::new(256, 48, 512, 48));
+    }
+
+    #[tokio::test]
+    async fn test_element_screenshot_from_segmentation() {
+        let settings = DaemonSettings::new("https://doesnotmatter.com").set_box_mode(BoxMode::Segmentation);
+        let (george, fake_daemon, vision) = george_with_settings(settings).await;
+
+        let mut screen = RgbImage::from_pixel(400, 200, Rgb([255, 255, 255]));
+        for x in 120..200 {
+            for y in 60..90 {
+                screen.put_pixel(x, y, Rgb([30, 90, 220]));
+            }
+        }
+        let mut png = Vec::new();
+        screen.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).unwrap();
+        fake_daemon.set_screenshot(&png);
+        vision.add_point("save button", 150, 70);
+
+        let element_box = george.element_box("save button").await.unwrap();
+        let element_screenshot = image::load_from_memory(&george.element_screenshot("save button").await.unwrap()).unwrap();
+
+        assert_eq!(element_box, ElementBox::new(120, 60, 80, 30));
+        assert_eq!((element_screenshot.width(), element_screenshot.height()), (80, 30));
+    }
+
+    #[tokio::test]
+    async fn test_click_with_agreeing_samples() {
+        let second_opinion = MockVisionBackend::new();
+        let verification = Verification::new().set_samples(3).add_backend(second_opinion.clone());
+        let settings = DaemonSettings::new("https://doesnotmatter.com").set_verification(verification);
+        let (george, fake_daemon, vision) = george_with_settings(settings).await;
+        vision.add_point("sign in button", 256, 192);
+        second_opinion.add_point("sign in button", 260, 196);
+
+        george.click("sign in button").await.unwrap();
+
+        assert_eq!(fake_daemon.actions(), vec![FakeDaemonAction::Click { x: 257, y: 193, options: ClickOptions::new() }]);
+    }
+
+    #[tokio::test]
+    async fn test_click_fails_with_low_confidence() {
+        let second_opinion = MockVisionBackend::new();
+        let verification = Verification::new().set_samples(2).add_backend(second_opinion.clone());
+        let settings = DaemonSettings::new("https://doesnotmatter.com").set_verification(verification);
+        let (mut george, fake_daemon, vision) = george_with_settings(settings).await;
+        george.set_retry_policy(RetryPolicy::new().set_max_attempts(2));
+        vision.add_point("sign in button", 256, 192);
+        second_opinion.add_point("sign in button", 768, 576);
+
+        let vote = george.locate_with_confidence("sign in button").await.unwrap();
+        let result = george.click("sign in button").await;
+
+        assert_eq!(vote.confidence, 0.5);
+        assert!(matches!(result, Err(DaemonError::LowConfidence { confidence, .. }) if confidence == 0.5));
+        assert!(fake_daemon.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_backend_takes_every_sample() {
+        let verification = Verification::new().set_samples(4);
+        let settings = DaemonSettings::new("https://doesnotmatter.com").set_verification(verification);
+        let (george, _fake_daemon, vision) = george_with_settings(settings).await;
+        vision.add_point("sign in button", 256, 192);
+
+        george.locate_with_confidence("sign in button").await.unwrap();
+
+        assert_eq!(vision.prompts().len(), 4);
+    }
+}
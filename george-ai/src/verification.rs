@@ -0,0 +1,180 @@
+use crate::vision::VisionBackend;
+use std::fmt;
+use std::sync::Arc;
+
+/// How many samples are taken unless set with [`Verification::set_samples`].
+const DEFAULT_SAMPLES: usize = 3;
+
+/// Locates every element several times and only trusts the location when most samples agree,
+/// failing with [`crate::DaemonError::LowConfidence`] instead of clicking the wrong thing.
+///
+/// Samples rotate through the prompt variants and the backends. Unless the number of samples is
+/// set, only one is taken when they would all be the same: with the built-in Molmo backend at a
+/// temperature of 0 and neither variants nor backends. Samples which fail count as disagreeing.
+///
+/// # Example
+///
+/// ```rust
+/// use george_ai::{DaemonSettings, MolmoBackend, Verification};
+///
+/// let second_opinion = MolmoBackend::new(DaemonSettings::new("https://your-other-llm.com"));
+/// let settings = DaemonSettings::new("https://your-molmo-llm.com").set_verification(
+///     Verification::new()
+///         .set_samples(4)
+///         .set_min_confidence(0.75)
+///         .add_prompt_variant(String::from("Point to the "))
+///         .add_backend(second_opinion),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct Verification {
+    samples: Option<usize>,
+    pub(crate) min_confidence: f64,
+    pub(crate) cluster_radius: u32,
+    pub(crate) prompt_variants: Vec<String>,
+    backends: Vec<Arc<dyn VisionBackend>>,
+}
+
+impl Default for Verification {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Verification {
+    /// Takes 3 samples and requires 2 of them to agree within 10 pixels.
+    pub fn new() -> Self {
+        Self {
+            samples: None,
+            min_confidence: 0.6,
+            cluster_radius: 10,
+            prompt_variants: Vec::new(),
+            backends: Vec::new(),
+        }
+    }
+
+    /// Sets how many times every element is located. The samples are taken even if they would
+    /// all be the same.
+    pub fn set_samples(mut self, samples: usize) -> Self {
+        self.samples = Some(samples.max(1));
+        self
+    }
+
+    /// Sets the share of samples (0.0 to 1.0) which must agree on the location.
+    pub fn set_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Sets how many pixels apart two samples may be and still agree.
+    pub fn set_cluster_radius(mut self, cluster_radius: u32) -> Self {
+        self.cluster_radius = cluster_radius;
+        self
+    }
+
+    /// Adds a prompt to sample with besides the coordinate prompt. The selector is appended.
+    pub fn add_prompt_variant(mut self, prompt_variant: String) -> Self {
+        self.prompt_variants.push(prompt_variant);
+        self
+    }
+
+    /// Adds a backend to sample with besides George's own.
+    pub fn add_backend(mut self, backend: impl VisionBackend + 'static) -> Self {
+        self.backends.push(Arc::new(backend));
+        self
+    }
+
+    /// Returns how many samples to take. `deterministic` tells whether the primary backend
+    /// always gives the same answer, in which case one sample is enough unless there are prompt
+    /// variants or backends, or the number of samples was set.
+    pub(crate) fn samples(&self, deterministic: bool) -> usize {
+        match self.samples {
+            Some(samples) => samples,
+            None if deterministic && self.prompt_variants.is_empty() && self.backends.is_empty() => 1,
+            None => DEFAULT_SAMPLES,
+        }
+    }
+
+    /// Returns the backend which takes the given sample, rotating from `primary` through the
+    /// added backends.
+    pub(crate) fn backend(&self, sample: usize, primary: &Arc<dyn VisionBackend>) -> Arc<dyn VisionBackend> {
+        match sample % (self.backends.len() + 1) {
+            0 => primary.clone(),
+            i => self.backends[i - 1].clone(),
+        }
+    }
+}
+
+impl fmt::Debug for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Verification")
+            .field("samples", &self.samples)
+            .field("min_confidence", &self.min_confidence)
+            .field("cluster_radius", &self.cluster_radius)
+            .field("prompt_variants", &self.prompt_variants)
+            .field("backends", &self.backends.len())
+            .finish()
+    }
+}
+
+/// Where the samples agreed an element is, and how many of them did.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vote {
+    /// The average of the agreeing samples in screen pixels.
+    pub point: (u32, u32),
+    /// The share of all samples (0.0 to 1.0) which agreed.
+    pub confidence: f64,
+}
+
+/// Finds the largest group of points within `radius` pixels of one of them. `samples` includes
+/// the samples which failed and returned no point.
+pub(crate) fn vote(points: &[(u32, u32)], samples: usize, radius: u32) -> Option<Vote> {
+    let near = |a: &(u32, u32), b: &(u32, u32)| {
+        let (dx, dy) = (a.0.abs_diff(b.0) as u64, a.1.abs_diff(b.1) as u64);
+        dx * dx + dy * dy <= radius as u64 * radius as u64
+    };
+
+    let cluster = points.iter()
+        .map(|center| points.iter().filter(|point| near(center, point)).collect::<Vec<_>>())
+        .reduce(|best, cluster| if cluster.len() > best.len() { cluster } else { best })?;
+
+    let count = cluster.len() as u64;
+    let x = cluster.iter().map(|point| point.0 as u64).sum::<u64>() / count;
+    let y = cluster.iter().map(|point| point.1 as u64).sum::<u64>() / count;
+
+    Some(Vote {
+        point: (x as u32, y as u32),
+        confidence: cluster.len() as f64 / samples.max(points.len()) as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_samples_are_taken_once() {
+        let verification = Verification::new();
+
+        assert_eq!(verification.samples(true), 1);
+        assert_eq!(verification.samples(false), 3);
+        assert_eq!(verification.clone().add_prompt_variant(String::from("Point to the ")).samples(true), 3);
+        assert_eq!(verification.set_samples(5).samples(true), 5);
+    }
+
+    #[test]
+    fn test_vote_averages_the_largest_cluster() {
+        let points = [(100, 100), (400, 300), (104, 98), (102, 102)];
+
+        assert_eq!(vote(&points, 5, 10), Some(Vote { point: (102, 100), confidence: 0.6 }));
+    }
+
+    #[test]
+    fn test_vote_without_agreement() {
+        let result = vote(&[(100, 100), (400, 300), (700, 500)], 3, 10).unwrap();
+
+        assert!((result.confidence - 1.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(result.point, (100, 100));
+        assert_eq!(vote(&[], 3, 10), None);
+    }
+}
@@ -1,19 +1,26 @@
+use crate::exec::{BackgroundProcess, ExecOptions, ExecOutput};
+use crate::logging::log;
 use bollard::{
-    network::CreateNetworkOptions,
+    network::{CreateNetworkOptions, ListNetworksOptions},
     models::{HostConfig, PortBinding},
-    image::BuildImageOptions,
-    container::{Config, CreateContainerOptions, StartContainerOptions},
-    exec::{CreateExecOptions, StartExecOptions},
+    image::{BuildImageOptions, CreateImageOptions, ListImagesOptions, RemoveImageOptions},
+    container::{
+        Config, CreateContainerOptions, DownloadFromContainerOptions, ListContainersOptions, RemoveContainerOptions,
+        StartContainerOptions, UploadToContainerOptions,
+    },
     Docker,
 };
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use std::fs::File;
-use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 use futures_util::StreamExt;
-use tar::Builder;
+use ignore::gitignore::GitignoreBuilder;
+use ignore::WalkBuilder;
+use sha2::{Digest, Sha256};
+use tar::{Archive, Builder};
 use thiserror::Error;
 use tokio::time::sleep;
 
@@ -27,35 +34,238 @@ pub enum VirtualMachineError {
     Port,
     #[error("Build error: {0}")]
     Build(String),
+    #[error("Virtual machine not started")]
+    NotStarted,
+    #[error("Container {0} is not running")]
+    ContainerNotRunning(String),
+    #[error("Command '{command}' timed out after {timeout:?}")]
+    ExecTimeout { command: String, timeout: Duration },
+    #[error("Image {0} not found locally and pulling is disabled")]
+    ImageNotFound(String),
+    #[error("Every session in the pool failed to reset and was removed")]
+    PoolExhausted,
 }
 
+/// The repository of the image built from the bundled Dockerfile, which builds george-daemon from
+/// the crate next to this one. It is tagged with a hash of the files it is built from, so it is
+/// built once and reused until the Dockerfile or the daemon changes.
+const DEFAULT_IMAGE_REPOSITORY: &str = "george-daemon";
+
+/// The label put on every container, network and image George creates.
+const LABEL: &str = "george";
+/// The label holding the session a container or network belongs to.
+const SESSION_LABEL: &str = "george.session";
+/// The label holding when a container or network was created, in seconds since the Unix epoch.
+const CREATED_LABEL: &str = "george.created";
+/// The label marking a named session's container and network, which are never swept as orphans.
+const PERSISTENT_LABEL: &str = "george.persistent";
+
+/// The repository of images layered on top of the base image with extra packages or a
+/// user-supplied Dockerfile. They are tagged with a hash of what they add.
+const CUSTOM_IMAGE_REPOSITORY: &str = "george-daemon-custom";
+
+/// The build argument holding the image a layered Dockerfile builds on.
+const BASE_IMAGE_ARG: &str = "GEORGE_BASE_IMAGE";
+
+/// The prefix of the images older versions of George built on every start.
+const PER_RUN_IMAGE_PREFIX: &str = "george-daemon-image-";
+
+/// When an image set with [`VirtualMachineSettings::set_image`] is pulled from its registry.
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImagePullPolicy {
+    /// Pulls the image before every start, picking up changes to mutable tags like `latest`.
+    Always,
+    /// Pulls the image only if it isn't available locally.
+    #[default]
+    IfNotPresent,
+    /// Never pulls the image, failing to start if it isn't available locally.
+    Never,
+}
+
+
+#[derive(Clone, Debug)]
+pub struct VirtualMachineSettings {
+    pub(crate) display_width: u32,
+    pub(crate) display_height: u32,
+    pub(crate) display_depth: u8,
+    pub(crate) session_name: Option<String>,
+    pub(crate) image: Option<String>,
+    pub(crate) image_pull_policy: ImagePullPolicy,
+    pub(crate) env: Vec<(String, String)>,
+    pub(crate) mounts: Vec<String>,
+    pub(crate) packages: Vec<String>,
+    pub(crate) dockerfile: Option<PathBuf>,
+}
+
+impl Default for VirtualMachineSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VirtualMachineSettings {
+    /// A 1024x768 virtual display with 16-bit color, running in an image built from the bundled
+    /// Dockerfile, which is rebuilt when the Dockerfile or george-daemon changes.
+    pub fn new() -> Self {
+        Self {
+            display_width: 1024,
+            display_height: 768,
+            display_depth: 16,
+            session_name: None,
+            image: None,
+            image_pull_policy: ImagePullPolicy::IfNotPresent,
+            env: Vec::new(),
+            mounts: Vec::new(),
+            packages: Vec::new(),
+            dockerfile: None,
+        }
+    }
+
+    /// Sets the resolution of the virtual display in pixels, e.g. 1920x1080 or a portrait
+    /// 390x844 mobile screen.
+    pub fn set_display_size(mut self, width: u32, height: u32) -> Self {
+        self.display_width = width;
+        self.display_height = height;
+        self
+    }
+
+    /// Sets the color depth of the virtual display in bits per pixel. Xvfb supports 8, 16 and 24.
+    pub fn set_display_depth(mut self, depth: u8) -> Self {
+        self.display_depth = depth;
+        self
+    }
+
+    /// Gives the session a stable name so its container outlives the process.
+    ///
+    /// Starting a named session reattaches to its container if it already exists, starting it
+    /// again if it has stopped, and `George::stop` leaves it running so the browser can be
+    /// inspected after a failure. Use `George::destroy` to remove it. A reattached container
+    /// keeps the display, image, environment variables and mounts it was created with.
+    pub fn set_session_name(mut self, session_name: String) -> Self {
+        self.session_name = Some(session_name);
+        self
+    }
+
+    /// Runs the container from an existing image, pulling it according to the image pull policy,
+    /// instead of the one built from the bundled Dockerfile. The image must contain Xvfb and
+    /// george-daemon like the bundled one does.
+    pub fn set_image(mut self, image: String) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    /// Sets when the image set with [`VirtualMachineSettings::set_image`] is pulled. Defaults to
+    /// [`ImagePullPolicy::IfNotPresent`].
+    pub fn set_image_pull_policy(mut self, image_pull_policy: ImagePullPolicy) -> Self {
+        self.image_pull_policy = image_pull_policy;
+        self
+    }
+
+    /// Sets an environment variable in the container.
+    pub fn add_env(mut self, name: String, value: String) -> Self {
+        self.env.push((name, value));
+        self
+    }
+
+    /// Mounts a host directory or file into the container.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_path` - The absolute path on the host.
+    /// * `container_path` - The absolute path inside the container.
+    /// * `read_only` - Whether the container is prevented from writing to the mount.
+    pub fn add_mount(mut self, host_path: &str, container_path: &str, read_only: bool) -> Self {
+        let mode = if read_only { "ro" } else { "rw" };
+        self.mounts.push(format!("{}:{}:{}", host_path, container_path, mode));
+        self
+    }
+
+    /// Installs an apt package, e.g. `libreoffice`, in an image layered on top of the base image.
+    /// The layer is built on the first start and reused until the base image changes.
+    pub fn add_package(mut self, package: String) -> Self {
+        self.packages.push(package);
+        self
+    }
+
+    /// Builds a Dockerfile on top of the base image (and any extra packages), e.g. to install an
+    /// application. The Dockerfile's directory is the build context, minus what its
+    /// `.dockerignore` excludes, and the image to build on is passed in the `GEORGE_BASE_IMAGE`
+    /// build argument:
+    ///
+    /// ```dockerfile
+    /// ARG GEORGE_BASE_IMAGE
+    /// FROM ${GEORGE_BASE_IMAGE}
+    /// COPY our-app.deb /tmp/
+    /// RUN apt-get update && apt-get install -y /tmp/our-app.deb
+    /// ```
+    ///
+    /// The image is rebuilt on every start so changes to the context are picked up, which is
+    /// fast when Docker's build cache is warm.
+    pub fn set_dockerfile(mut self, dockerfile: impl Into<PathBuf>) -> Self {
+        self.dockerfile = Some(dockerfile.into());
+        self
+    }
+}
 
 pub struct VirtualMachine {
     docker: Docker,
+    session: String,
     container_name: String,
     pub port: Option<String>,
     network_name: String,
-    id: Uuid,
+    settings: VirtualMachineSettings,
+    /// Whether the network, and possibly the container, exist and should be removed on drop.
+    created: bool,
 }
 
 impl VirtualMachine {
-    pub fn new() -> Self {
-        let id = Uuid::new_v4();
+    pub fn with_settings(settings: VirtualMachineSettings) -> Result<Self, VirtualMachineError> {
+        let session = match &settings.session_name {
+            Some(session_name) => session_name.clone(),
+            None => Uuid::new_v4().to_string(),
+        };
 
-        Self {
-            id,
-            docker: Docker::connect_with_local_defaults().expect("Failed to connect to Docker"),
-            container_name: format!("george-daemon-container-{}", id),
+        Ok(Self {
+            docker: Docker::connect_with_local_defaults()?,
+            container_name: format!("george-daemon-container-{}", session),
             port: None,
-            network_name: format!("george-network-{}", id),
-        }
+            network_name: format!("george-network-{}", session),
+            session,
+            settings,
+            created: false,
+        })
     }
 
     pub async fn start(&mut self) -> Result<(), VirtualMachineError> {
-        let image_name = format!("george-daemon-image-{}", self.id);
+        if self.settings.session_name.is_some() && self.reattach().await? {
+            return Ok(());
+        }
+
+        let mut image_name = match &self.settings.image {
+            Some(image) => {
+                self.pull_image(image).await?;
+                image.clone()
+            }
+            None => {
+                let files = default_image_files(Path::new(env!("CARGO_MANIFEST_DIR")), &daemon_dir())?;
+                let image_name = default_image_tag(&files);
+                if !self.image_exists(&image_name).await? {
+                    self.build_image(&image_name, &files).await?;
+                    self.remove_stale_default_images(&image_name).await;
+                }
+                image_name
+            }
+        };
+        if !self.settings.packages.is_empty() {
+            image_name = self.build_packages_layer(&image_name).await?;
+        }
+        if let Some(dockerfile) = &self.settings.dockerfile {
+            image_name = self.build_dockerfile_layer(&image_name, dockerfile).await?;
+        }
 
-        self.build_image(&image_name).await?;
         self.create_network().await?;
+        self.created = true;
         self.create_container(&image_name).await?;
         self.docker.start_container(&self.container_name, None::<StartContainerOptions<String>>).await?;
         self.extract_port().await?;
@@ -63,26 +273,155 @@ impl VirtualMachine {
         Ok(())
     }
 
-    async fn build_image(&self, image_name: &str) -> Result<(), VirtualMachineError> {
-        let project_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// Reattaches to the session's container if it exists, starting it if it has stopped.
+    /// Returns whether there was a container to reattach to.
+    async fn reattach(&mut self) -> Result<bool, VirtualMachineError> {
+        let container_info = match self.docker.inspect_container(&self.container_name, None).await {
+            Ok(container_info) => container_info,
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let running = container_info.state.and_then(|state| state.running).unwrap_or(false);
+        if !running {
+            self.docker.start_container(&self.container_name, None::<StartContainerOptions<String>>).await?;
+        }
+        self.extract_port().await?;
+        log!("Reattached to container {}", self.container_name);
+
+        Ok(true)
+    }
+
+    async fn image_exists(&self, image_name: &str) -> Result<bool, VirtualMachineError> {
+        match self.docker.inspect_image(image_name).await {
+            Ok(_) => Ok(true),
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn pull_image(&self, image_name: &str) -> Result<(), VirtualMachineError> {
+        let pull = match self.settings.image_pull_policy {
+            ImagePullPolicy::Always => true,
+            ImagePullPolicy::IfNotPresent => !self.image_exists(image_name).await?,
+            ImagePullPolicy::Never if self.image_exists(image_name).await? => false,
+            ImagePullPolicy::Never => return Err(VirtualMachineError::ImageNotFound(image_name.to_string())),
+        };
+        if !pull {
+            return Ok(());
+        }
+
+        log!("Pulling image {}", image_name);
+        let mut pull_stream = self.docker.create_image(
+            Some(CreateImageOptions { from_image: image_name, ..Default::default() }),
+            None,
+            None,
+        );
+        while let Some(pull_result) = pull_stream.next().await {
+            pull_result?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the default images George built from older Dockerfiles or daemons than `current`,
+    /// and the images older versions of George built on every start. Images still used by a
+    /// container are left alone.
+    async fn remove_stale_default_images(&self, current: &str) {
+        let images = match self.docker.list_images(None::<ListImagesOptions<String>>).await {
+            Ok(images) => images,
+            Err(e) => return eprintln!("Failed to list images: {}", e),
+        };
+
+        let default_image_prefix = &format!("{}:", DEFAULT_IMAGE_REPOSITORY);
+        let stale_images = images.iter().flat_map(|image| {
+            let built_by_george = image.labels.contains_key(LABEL);
+            image.repo_tags.iter().filter(move |tag| {
+                let stale_default = built_by_george && tag.starts_with(default_image_prefix.as_str()) && *tag != current;
+                tag.starts_with(PER_RUN_IMAGE_PREFIX) || stale_default
+            })
+        });
+        for tag in stale_images {
+            match self.docker.remove_image(tag, None::<RemoveImageOptions>, None).await {
+                Ok(_) => log!("Image {} removed", tag),
+                Err(e) => eprintln!("Failed to remove image {}: {}", tag, e),
+            }
+        }
+    }
+
+    /// Builds the default image from the files returned by [`default_image_files`].
+    async fn build_image(&self, image_name: &str, files: &[(String, Vec<u8>)]) -> Result<(), VirtualMachineError> {
+        let mut builder = Builder::new(Vec::new());
+        for (name, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, contents.as_slice())?;
+        }
+
+        self.build(image_name, "Dockerfile", builder.into_inner()?, None).await
+    }
+
+    /// Builds an image with the extra packages on top of `base_image`, unless it already exists.
+    /// The layer is cached by the base image's ID, so it is rebuilt when the base changes.
+    async fn build_packages_layer(&self, base_image: &str) -> Result<String, VirtualMachineError> {
+        let dockerfile = packages_dockerfile(&self.settings.packages);
+        let image_name = layer_tag(&self.image_id(base_image).await?, dockerfile.as_bytes());
+        if self.image_exists(&image_name).await? {
+            return Ok(image_name);
+        }
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(dockerfile.len() as u64);
+        header.set_mode(0o644);
+        let mut builder = Builder::new(Vec::new());
+        builder.append_data(&mut header, "Dockerfile", dockerfile.as_bytes())?;
+
+        self.build(&image_name, "Dockerfile", builder.into_inner()?, Some(base_image)).await?;
+        Ok(image_name)
+    }
+
+    /// Builds the user's Dockerfile on top of `base_image` with the Dockerfile's directory as the
+    /// build context, leaving out what its `.dockerignore` excludes.
+    async fn build_dockerfile_layer(&self, base_image: &str, dockerfile: &Path) -> Result<String, VirtualMachineError> {
+        let context = dockerfile.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let dockerfile_name = dockerfile.file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| VirtualMachineError::Build(format!("Invalid Dockerfile path {}", dockerfile.display())))?;
+
+        let context = build_context(context, dockerfile_name)?;
+        let image_name = layer_tag(&self.image_id(base_image).await?, &context);
+
+        self.build(&image_name, dockerfile_name, context, Some(base_image)).await?;
+        Ok(image_name)
+    }
 
-        let tar_path = Path::new("/tmp/george-daemon.tar");
-        let mut builder = Builder::new(File::create(tar_path)?);
-        builder.append_dir_all(".", project_root)?;
-        builder.finish()?;
+    async fn image_id(&self, image_name: &str) -> Result<String, VirtualMachineError> {
+        self.docker.inspect_image(image_name).await?.id
+            .ok_or_else(|| VirtualMachineError::ImageNotFound(image_name.to_string()))
+    }
 
-        let tar_contents = std::fs::read(tar_path)?;
-        let build_options = BuildImageOptions::<&str> { dockerfile: "Dockerfile", t: image_name, ..Default::default() };
+    async fn build(&self, image_name: &str, dockerfile: &str, context: Vec<u8>, base_image: Option<&str>) -> Result<(), VirtualMachineError> {
+        log!("Building image {}", image_name);
+        let build_options = BuildImageOptions::<&str> {
+            dockerfile,
+            t: image_name,
+            labels: HashMap::from([(LABEL, "true")]),
+            buildargs: base_image.map(|base_image| HashMap::from([(BASE_IMAGE_ARG, base_image)])).unwrap_or_default(),
+            ..Default::default()
+        };
 
         let mut build_stream = self.docker.build_image(
             build_options,
             None,
-            Some(tar_contents.into()),
+            Some(context.into()),
         );
 
         while let Some(build_result) = build_stream.next().await {
             match build_result {
-                Ok(_) => {},
+                Ok(build_info) => if let Some(error) = build_info.error {
+                    return Err(VirtualMachineError::Build(error));
+                },
                 Err(e) => return Err(VirtualMachineError::Build(format!("{:?}", e))),
             }
         }
@@ -91,12 +430,33 @@ impl VirtualMachine {
     }
 
     async fn create_network(&self) -> Result<(), VirtualMachineError> {
-        self.docker.create_network(CreateNetworkOptions {
+        let labels = self.labels();
+        let result = self.docker.create_network(CreateNetworkOptions {
             name: self.network_name.as_str(),
+            labels: labels.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect(),
             ..Default::default()
-        }).await?;
+        }).await;
 
-        Ok(())
+        match result {
+            Ok(_) => Ok(()),
+            // A named session's network survives its container being removed by hand.
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 409, .. })
+                if self.settings.session_name.is_some() => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The labels identifying this session's container and network.
+    fn labels(&self) -> HashMap<String, String> {
+        let mut labels = HashMap::from([
+            (LABEL.to_string(), String::from("true")),
+            (SESSION_LABEL.to_string(), self.session.clone()),
+            (CREATED_LABEL.to_string(), unix_time().to_string()),
+        ]);
+        if self.settings.session_name.is_some() {
+            labels.insert(PERSISTENT_LABEL.to_string(), String::from("true"));
+        }
+        labels
     }
 
     async fn create_container(&self, image_name: &str) -> Result<(), VirtualMachineError> {
@@ -115,19 +475,28 @@ impl VirtualMachine {
         let host_config = HostConfig {
             port_bindings: Some(port_bindings),
             network_mode: Some(self.network_name.clone()),
+            binds: Some(self.settings.mounts.clone()).filter(|mounts| !mounts.is_empty()),
             ..Default::default()
         };
 
+        let env = std::iter::once(String::from("DISPLAY=:99"))
+            .chain(self.settings.env.iter().map(|(name, value)| format!("{}={}", name, value)))
+            .collect();
+
         self.docker.create_container(
             Some(CreateContainerOptions { name: self.container_name.clone(), platform: None }),
             Config {
                 image: Some(image_name.to_string()),
                 exposed_ports: Some(exposed_ports),
                 host_config: Some(host_config),
-                env: Some(vec!["DISPLAY=:99".to_string()]),
+                env: Some(env),
+                labels: Some(self.labels()),
                 cmd: Some(vec![
                     String::from("sh"), String::from("-c"),
-                    String::from("Xvfb :99 -screen 0 1024x768x16 & sleep 2 && ./george-daemon")
+                    format!(
+                        "Xvfb :99 -screen 0 {}x{}x{} & sleep 2 && ./george-daemon",
+                        self.settings.display_width, self.settings.display_height, self.settings.display_depth
+                    )
                 ]),
                 ..Default::default()
             },
@@ -136,41 +505,54 @@ impl VirtualMachine {
         Ok(())
     }
 
-    pub async fn execute(&self, command: &str, wait_for_output: bool) -> Result<String, VirtualMachineError> {
+    /// Runs a command with `sh -c` and waits for it to exit.
+    pub async fn execute(&self, command: &str, options: &ExecOptions) -> Result<ExecOutput, VirtualMachineError> {
+        self.spawn(command, options).await?.wait_with_timeout(options.timeout).await
+    }
+
+    /// Starts a command with `sh -c` without waiting for it to exit.
+    pub async fn spawn(&self, command: &str, options: &ExecOptions) -> Result<BackgroundProcess, VirtualMachineError> {
         let container_info = self.docker.inspect_container(&self.container_name, None).await?;
-        if !container_info.state.unwrap().running.unwrap_or(false) {
-            return Err(VirtualMachineError::Docker(bollard::errors::Error::IOError {
-                err: std::io::Error::new(std::io::ErrorKind::Other, "Container is not running"),
-            }));
+        if !container_info.state.and_then(|state| state.running).unwrap_or(false) {
+            return Err(VirtualMachineError::ContainerNotRunning(self.container_name.clone()));
         }
 
-        let exec = self.docker.create_exec(
+        BackgroundProcess::start(&self.docker, &self.container_name, command, options).await
+    }
+
+    /// Copies a file or directory from the host into the container, creating the container
+    /// path's parent directories and replacing whatever is at the container path.
+    pub async fn upload(&self, local_path: &Path, container_path: &str) -> Result<(), VirtualMachineError> {
+        let (parent, name) = split_container_path(container_path)?;
+        let archive = pack(local_path, name)?;
+
+        self.execute(&format!("mkdir -p {}", shell_quote(parent)), &ExecOptions::new()).await?;
+        self.docker.upload_to_container(
             &self.container_name,
-            CreateExecOptions {
-                cmd: Some(vec!["sh", "-c", command]),
-                attach_stdout: Some(wait_for_output),
-                attach_stderr: Some(wait_for_output),
-                ..Default::default()
-            },
+            Some(UploadToContainerOptions { path: parent, ..Default::default() }),
+            archive.into(),
         ).await?;
+        log!("Uploaded {} to {}", local_path.display(), container_path);
 
-        let output = self.docker.start_exec(&exec.id, None::<StartExecOptions>).await?;
+        Ok(())
+    }
 
-        if wait_for_output {
-            if let bollard::exec::StartExecResults::Attached { mut output, .. } = output {
-                let mut result = String::new();
-                while let Some(Ok(output_chunk)) = output.next().await {
-                    result.push_str(&output_chunk.to_string());
-                }
-                Ok(result)
-            } else {
-                Err(VirtualMachineError::Docker(bollard::errors::Error::IOError {
-                    err: std::io::Error::new(std::io::ErrorKind::Other, "Failed to get command output"),
-                }))
-            }
-        } else {
-            Ok(String::from("Command started, not waiting for output"))
+    /// Copies a file or directory from the container to the host, creating the local path's
+    /// parent directories.
+    pub async fn download(&self, container_path: &str, local_path: &Path) -> Result<(), VirtualMachineError> {
+        let mut download_stream = self.docker.download_from_container(
+            &self.container_name,
+            Some(DownloadFromContainerOptions { path: container_path }),
+        );
+
+        let mut archive = Vec::new();
+        while let Some(chunk) = download_stream.next().await {
+            archive.extend_from_slice(&chunk?);
         }
+        unpack(&archive, local_path)?;
+        log!("Downloaded {} to {}", container_path, local_path.display());
+
+        Ok(())
     }
 
     async fn extract_port(&mut self) -> Result<(), VirtualMachineError> {
@@ -184,7 +566,7 @@ impl VirtualMachine {
                     if attempt == MAX_RETRIES {
                         return Err(e);
                     }
-                    println!("Failed to extract port (attempt {}), retrying...", attempt);
+                    log!("Failed to extract port (attempt {}), retrying...", attempt);
                     sleep(RETRY_DELAY).await;
                 }
             }
@@ -208,12 +590,24 @@ impl VirtualMachine {
         Ok(())
     }
 
+    /// Stops and removes the container and its network, unless this is a named session, which is
+    /// left running.
     pub async fn stop(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.settings.session_name.is_some() {
+            log!("Leaving container {} running", self.container_name);
+            return Ok(());
+        }
+
+        self.destroy().await
+    }
+
+    /// Stops and removes the container and its network, including a named session's.
+    pub async fn destroy(&mut self) -> Result<(), Box<dyn Error>> {
         match self.docker.stop_container(&self.container_name, None).await {
-            Ok(_) => println!("Container {} stopped", self.container_name),
+            Ok(_) => log!("Container {} stopped", self.container_name),
             Err(e) => {
                 if e.to_string().contains("container is not running") {
-                    println!("Container {} was already stopped", self.container_name);
+                    log!("Container {} was already stopped", self.container_name);
                 } else {
                     eprintln!("Error stopping container {}: {}", self.container_name, e);
                 }
@@ -221,17 +615,489 @@ impl VirtualMachine {
         }
 
         match self.docker.remove_container(&self.container_name, None).await {
-            Ok(_) => println!("Container {} removed", self.container_name),
+            Ok(_) => log!("Container {} removed", self.container_name),
             Err(e) => return Err(Box::new(e)),
         }
 
         match self.docker.remove_network(&self.network_name).await {
-            Ok(_) => println!("Network {} removed", self.network_name),
+            Ok(_) => log!("Network {} removed", self.network_name),
             Err(e) => eprintln!("Failed to remove network {}: {}", self.network_name, e),
         }
 
-        println!("Container {} and associated resources cleaned up", self.container_name);
+        self.created = false;
+        log!("Container {} and associated resources cleaned up", self.container_name);
 
         Ok(())
     }
+
+    /// Removes the containers and networks George created more than `older_than` ago, e.g. ones
+    /// left behind by a process which was killed. Named sessions are never removed. Layer images
+    /// built for extra packages or a Dockerfile, per-run images of older versions and untagged
+    /// images George built that old are removed too unless a container still uses them. Returns
+    /// the names of the removed containers, networks and images.
+    ///
+    /// Dropping a virtual machine which wasn't stopped only removes it on a best-effort basis, so
+    /// this sweep is what reliably cleans up after killed or crashed processes.
+    pub async fn cleanup_orphans(older_than: Duration) -> Result<Vec<String>, VirtualMachineError> {
+        let docker = Docker::connect_with_local_defaults()?;
+        let cutoff = unix_time().saturating_sub(older_than.as_secs());
+        let mut removed = Vec::new();
+
+        let containers = docker.list_containers(Some(ListContainersOptions {
+            all: true,
+            filters: HashMap::from([("label", vec![LABEL])]),
+            ..Default::default()
+        })).await?;
+        for container in containers {
+            if !is_orphan(&container.labels.unwrap_or_default(), cutoff) {
+                continue;
+            }
+            let Some(id) = container.id else { continue };
+            let name = container.names.and_then(|names| names.into_iter().next())
+                .map(|name| name.trim_start_matches('/').to_string())
+                .unwrap_or(id.clone());
+
+            let options = RemoveContainerOptions { force: true, ..Default::default() };
+            match docker.remove_container(&id, Some(options)).await {
+                Ok(_) => {
+                    log!("Container {} removed", name);
+                    removed.push(name);
+                }
+                Err(e) => eprintln!("Failed to remove container {}: {}", name, e),
+            }
+        }
+
+        let networks = docker.list_networks(Some(ListNetworksOptions {
+            filters: HashMap::from([("label", vec![LABEL])]),
+        })).await?;
+        for network in networks {
+            if !is_orphan(&network.labels.unwrap_or_default(), cutoff) {
+                continue;
+            }
+            let Some(name) = network.name else { continue };
+
+            match docker.remove_network(&name).await {
+                Ok(_) => {
+                    log!("Network {} removed", name);
+                    removed.push(name);
+                }
+                Err(e) => eprintln!("Failed to remove network {}: {}", name, e),
+            }
+        }
+
+        let in_use = docker.list_containers(Some(ListContainersOptions::<String> { all: true, ..Default::default() })).await?
+            .into_iter()
+            .filter_map(|container| container.image_id)
+            .collect::<HashSet<_>>();
+        // Images older versions of George built on every start may not carry the label, so every
+        // image is listed and matched by its tags.
+        let images = docker.list_images(None::<ListImagesOptions<String>>).await?;
+        for image in images {
+            if in_use.contains(&image.id) || image.created.max(0) as u64 > cutoff {
+                continue;
+            }
+
+            let dangling = image.repo_tags.iter().all(|tag| tag == "<none>:<none>");
+            if dangling && image.labels.contains_key(LABEL) {
+                match docker.remove_image(&image.id, None::<RemoveImageOptions>, None).await {
+                    Ok(_) => {
+                        log!("Image {} removed", image.id);
+                        removed.push(image.id.clone());
+                    }
+                    Err(e) => eprintln!("Failed to remove image {}: {}", image.id, e),
+                }
+                continue;
+            }
+
+            for tag in image.repo_tags.iter().filter(|tag| is_orphan_image_tag(tag)) {
+                match docker.remove_image(tag, None::<RemoveImageOptions>, None).await {
+                    Ok(_) => {
+                        log!("Image {} removed", tag);
+                        removed.push(tag.clone());
+                    }
+                    Err(e) => eprintln!("Failed to remove image {}: {}", tag, e),
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+impl Drop for VirtualMachine {
+    /// Removes the container and network if the virtual machine wasn't stopped, e.g. because a
+    /// test panicked. This is best-effort: it runs on a detached thread which is killed if the
+    /// process exits first, e.g. at the end of a test binary. Call [`VirtualMachine::stop`] to make
+    /// sure they are gone, and run [`VirtualMachine::cleanup_orphans`] to sweep what is left.
+    fn drop(&mut self) {
+        if !self.created || self.settings.session_name.is_some() {
+            return;
+        }
+
+        let container_name = self.container_name.clone();
+        let network_name = self.network_name.clone();
+
+        // The caller's runtime may be single threaded or shutting down, so tear down on a fresh
+        // runtime and Docker client on another thread.
+        std::thread::spawn(move || {
+            let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() else { return };
+            runtime.block_on(async {
+                let Ok(docker) = Docker::connect_with_local_defaults() else { return };
+                let options = RemoveContainerOptions { force: true, ..Default::default() };
+                if let Err(e) = docker.remove_container(&container_name, Some(options)).await {
+                    eprintln!("Failed to remove container {}: {}", container_name, e);
+                }
+                if let Err(e) = docker.remove_network(&network_name).await {
+                    eprintln!("Failed to remove network {}: {}", network_name, e);
+                }
+            });
+        });
+        log!("Cleaning up container {} and associated resources on drop", self.container_name);
+    }
+}
+
+/// Splits an absolute container path into its parent directory and file name.
+fn split_container_path(container_path: &str) -> Result<(&str, &str), VirtualMachineError> {
+    let invalid = || VirtualMachineError::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Expected an absolute path to a file or directory, got '{}'", container_path),
+    ));
+
+    let (parent, name) = container_path.trim_end_matches('/').rsplit_once('/').ok_or_else(invalid)?;
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(invalid());
+    }
+
+    Ok((if parent.is_empty() { "/" } else { parent }, name))
+}
+
+/// A Dockerfile which installs `packages` with apt on top of the base image.
+fn packages_dockerfile(packages: &[String]) -> String {
+    let packages: Vec<String> = packages.iter().map(|package| shell_quote(package)).collect();
+
+    format!(
+        "ARG {arg}\nFROM ${{{arg}}}\nRUN apt-get update && apt-get install -y {} && rm -rf /var/lib/apt/lists/*\n",
+        packages.join(" "),
+        arg = BASE_IMAGE_ARG,
+    )
+}
+
+/// The tag for an image which builds `context` on top of the image with ID `base_image_id`.
+fn layer_tag(base_image_id: &str, context: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(base_image_id.as_bytes());
+    hasher.update(context);
+
+    format!("{}:{:x}", CUSTOM_IMAGE_REPOSITORY, hasher.finalize())
+}
+
+/// Archives a Dockerfile's build context, leaving out the files matched by its `.dockerignore`.
+/// Patterns are relative to the context like Docker's, but a directory which is excluded can't
+/// have files re-included with `!`. The Dockerfile and `.dockerignore` are always sent.
+fn build_context(context: &Path, dockerfile_name: &str) -> Result<Vec<u8>, VirtualMachineError> {
+    let mut ignore = GitignoreBuilder::new(context);
+    if let Ok(patterns) = std::fs::read_to_string(context.join(".dockerignore")) {
+        for pattern in patterns.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let (negation, pattern) = match pattern.strip_prefix('!') {
+                Some(pattern) => ("!", pattern),
+                None => ("", pattern),
+            };
+            // Docker anchors every pattern to the root of the context.
+            ignore.add_line(None, &format!("{}/{}", negation, pattern.trim_start_matches('/')))
+                .map_err(|e| VirtualMachineError::Build(format!("Invalid .dockerignore pattern '{}': {}", pattern, e)))?;
+        }
+    }
+    let ignore = ignore.build()
+        .map_err(|e| VirtualMachineError::Build(format!("Invalid .dockerignore: {}", e)))?;
+
+    let root = context.to_path_buf();
+    let always_sent = [dockerfile_name.to_string(), String::from(".dockerignore")];
+    let walk = WalkBuilder::new(context)
+        .standard_filters(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|file_type| file_type.is_dir());
+            let is_always_sent = entry.depth() == 1 && always_sent.iter().any(|name| entry.file_name() == name.as_str());
+            entry.depth() == 0 || is_always_sent || !ignore.matched(entry.path(), is_dir).is_ignore()
+        })
+        .build();
+
+    let mut builder = Builder::new(Vec::new());
+    for entry in walk {
+        let entry = entry.map_err(|e| VirtualMachineError::Build(format!("Failed to read the build context: {}", e)))?;
+        if entry.file_type().is_some_and(|file_type| file_type.is_file()) {
+            let name = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+            builder.append_path_with_name(entry.path(), name)?;
+        }
+    }
+
+    Ok(builder.into_inner()?)
+}
+
+/// The george-daemon crate next to this one, which the default image builds the daemon from.
+fn daemon_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("george-daemon")
+}
+
+/// The tag for the default image built from `files`, a hash of their names and contents.
+fn default_image_tag(files: &[(String, Vec<u8>)]) -> String {
+    let mut hasher = Sha256::new();
+    for (name, contents) in files {
+        hasher.update(name.as_bytes());
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(contents);
+    }
+
+    format!("{}:{:x}", DEFAULT_IMAGE_REPOSITORY, hasher.finalize())
+}
+
+/// Reads the files the default image is built from: the bundled Dockerfile and the sources of the
+/// george-daemon crate, which the Dockerfile builds the daemon from. Returns them sorted by their
+/// name in the build context.
+fn default_image_files(manifest_dir: &Path, daemon_dir: &Path) -> Result<Vec<(String, Vec<u8>)>, VirtualMachineError> {
+    if !daemon_dir.join("Cargo.toml").is_file() {
+        return Err(VirtualMachineError::Build(format!(
+            "The george-daemon crate the default image is built from was not found at {}", daemon_dir.display()
+        )));
+    }
+
+    let mut files = vec![(String::from("Dockerfile"), std::fs::read(manifest_dir.join("Dockerfile"))?)];
+    for name in ["Cargo.toml", "Cargo.lock"] {
+        if daemon_dir.join(name).is_file() {
+            files.push((format!("george-daemon/{}", name), std::fs::read(daemon_dir.join(name))?));
+        }
+    }
+
+    let walk = WalkBuilder::new(daemon_dir.join("src"))
+        .standard_filters(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    for entry in walk {
+        let entry = entry.map_err(|e| VirtualMachineError::Build(format!("Failed to read the daemon's sources: {}", e)))?;
+        if entry.file_type().is_some_and(|file_type| file_type.is_file()) {
+            let name = entry.path().strip_prefix(daemon_dir).unwrap_or(entry.path());
+            files.push((format!("george-daemon/{}", name.display()), std::fs::read(entry.path())?));
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Archives a file or directory as a tar with a single top level entry called `name`.
+fn pack(local_path: &Path, name: &str) -> std::io::Result<Vec<u8>> {
+    let mut builder = Builder::new(Vec::new());
+    if local_path.is_dir() {
+        builder.append_dir_all(name, local_path)?;
+    } else {
+        builder.append_path_with_name(local_path, name)?;
+    }
+
+    builder.into_inner()
+}
+
+/// Extracts a tar with a single top level entry, as returned by Docker's archive API, to
+/// `local_path`.
+fn unpack(archive: &[u8], local_path: &Path) -> std::io::Result<()> {
+    let mut archive = Archive::new(archive);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let mut components = path.components();
+        components.next();
+
+        let relative_path = components.as_path();
+        if relative_path.components().any(|component| !matches!(component, Component::Normal(_))) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Refusing to extract '{}' outside of {}", path.display(), local_path.display()),
+            ));
+        }
+
+        let destination = if relative_path.as_os_str().is_empty() {
+            local_path.to_path_buf()
+        } else {
+            local_path.join(relative_path)
+        };
+        if entry.header().entry_type().is_dir() {
+            std::fs::create_dir_all(&destination)?;
+        } else {
+            if let Some(parent) = destination.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            entry.unpack(&destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
+
+/// Whether a resource with these labels was created before `cutoff` and isn't a named session.
+fn is_orphan(labels: &HashMap<String, String>, cutoff: u64) -> bool {
+    if labels.contains_key(PERSISTENT_LABEL) {
+        return false;
+    }
+
+    labels.get(CREATED_LABEL)
+        .and_then(|created| created.parse::<u64>().ok())
+        .is_some_and(|created| created <= cutoff)
+}
+
+/// Whether an image tag George built may be swept as an orphan: the layers it builds for extra
+/// packages or a Dockerfile, which get a new tag whenever what they add changes, and the images
+/// older versions of George built on every start.
+fn is_orphan_image_tag(tag: &str) -> bool {
+    let is_layer = tag.strip_prefix(CUSTOM_IMAGE_REPOSITORY).is_some_and(|rest| rest.starts_with(':'));
+
+    is_layer || tag.starts_with(PER_RUN_IMAGE_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_orphan() {
+        let labels = |entries: &[(&str, &str)]| entries.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+
+        assert!(is_orphan(&labels(&[(LABEL, "true"), (CREATED_LABEL, "100")]), 200));
+        assert!(!is_orphan(&labels(&[(LABEL, "true"), (CREATED_LABEL, "300")]), 200));
+        assert!(!is_orphan(&labels(&[(LABEL, "true"), (CREATED_LABEL, "100"), (PERSISTENT_LABEL, "true")]), 200));
+        assert!(!is_orphan(&labels(&[(LABEL, "true")]), 200));
+    }
+
+    #[test]
+    fn test_packages_dockerfile() {
+        let dockerfile = packages_dockerfile(&[String::from("libreoffice"), String::from("fonts-noto")]);
+
+        assert_eq!(dockerfile, "ARG GEORGE_BASE_IMAGE\nFROM ${GEORGE_BASE_IMAGE}\n\
+            RUN apt-get update && apt-get install -y 'libreoffice' 'fonts-noto' && rm -rf /var/lib/apt/lists/*\n");
+    }
+
+    #[test]
+    fn test_layer_tag() {
+        let tag = layer_tag("sha256:1111", b"RUN true");
+
+        assert_eq!(tag, "george-daemon-custom:fc7977982e31df7958ca6b4561d3d07988751ae4e5a32c2dafdb8a73ebb72007");
+        assert_eq!(tag, layer_tag("sha256:1111", b"RUN true"));
+        assert_ne!(tag, layer_tag("sha256:2222", b"RUN true"));
+        assert_ne!(tag, layer_tag("sha256:1111", b"RUN false"));
+    }
+
+    #[test]
+    fn test_build_context_honours_dockerignore() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("Dockerfile"), "COPY . /app").unwrap();
+        std::fs::write(dir.join(".dockerignore"), "# Build output\ntarget\n*.log\n!keep.log\nDockerfile\n").unwrap();
+        std::fs::write(dir.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.join("debug.log"), "noise").unwrap();
+        std::fs::write(dir.join("keep.log"), "signal").unwrap();
+        std::fs::create_dir_all(dir.join("target/debug")).unwrap();
+        std::fs::write(dir.join("target/debug/george"), "binary").unwrap();
+        std::fs::create_dir_all(dir.join("src/target")).unwrap();
+        std::fs::write(dir.join("src/target/lib.rs"), "").unwrap();
+
+        let context = build_context(&dir, "Dockerfile").unwrap();
+        let mut archive = Archive::new(context.as_slice());
+        let names = archive.entries().unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec![".dockerignore", "Dockerfile", "keep.log", "main.rs", "src/target/lib.rs"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_image_tag_changes_with_the_files() {
+        let files = vec![
+            (String::from("Dockerfile"), b"FROM ubuntu:22.04".to_vec()),
+            (String::from("george-daemon/src/main.rs"), b"fn main() {}".to_vec()),
+        ];
+        let mut changed = files.clone();
+        changed[1].1 = b"fn main() { serve() }".to_vec();
+
+        assert!(default_image_tag(&files).starts_with("george-daemon:"));
+        assert_eq!(default_image_tag(&files), default_image_tag(&files.clone()));
+        assert_ne!(default_image_tag(&files), default_image_tag(&changed));
+    }
+
+    #[test]
+    fn test_default_image_files_include_the_daemon_sources() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let files = default_image_files(manifest_dir, &daemon_dir()).unwrap();
+        let names = files.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+
+        assert!(names.contains(&"Dockerfile"));
+        assert!(names.contains(&"george-daemon/Cargo.toml"));
+        assert!(names.contains(&"george-daemon/src/main.rs"));
+        assert!(names.contains(&"george-daemon/src/routes/click_route.rs"));
+        assert!(!names.iter().any(|name| name.starts_with("george-daemon/target")));
+
+        let dir = temp_dir();
+        assert!(matches!(default_image_files(manifest_dir, &dir), Err(VirtualMachineError::Build(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("george-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_is_orphan_image_tag() {
+        assert!(is_orphan_image_tag(&layer_tag("sha256:1111", b"RUN true")));
+        assert!(is_orphan_image_tag("george-daemon-image-4f1c2a:latest"));
+        assert!(!is_orphan_image_tag("george-daemon-customized:latest"));
+        assert!(!is_orphan_image_tag("ubuntu:22.04"));
+    }
+
+    #[test]
+    fn test_split_container_path() {
+        assert_eq!(split_container_path("/root/Downloads/report.pdf").unwrap(), ("/root/Downloads", "report.pdf"));
+        assert_eq!(split_container_path("/root/fixtures/").unwrap(), ("/root", "fixtures"));
+        assert_eq!(split_container_path("/report.pdf").unwrap(), ("/", "report.pdf"));
+        assert!(split_container_path("report.pdf").is_err());
+        assert!(split_container_path("/").is_err());
+    }
+
+    #[test]
+    fn test_pack_and_unpack_binary_file() {
+        let dir = temp_dir();
+        let contents: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+        std::fs::write(dir.join("image.png"), &contents).unwrap();
+
+        let archive = pack(&dir.join("image.png"), "upload.png").unwrap();
+        unpack(&archive, &dir.join("downloaded.png")).unwrap();
+
+        assert_eq!(std::fs::read(dir.join("downloaded.png")).unwrap(), contents);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_pack_and_unpack_directory() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join("fixtures/nested/empty")).unwrap();
+        std::fs::write(dir.join("fixtures/a.txt"), "a").unwrap();
+        std::fs::write(dir.join("fixtures/nested/b.bin"), [0u8, 159, 146, 150]).unwrap();
+
+        let archive = pack(&dir.join("fixtures"), "fixtures").unwrap();
+        unpack(&archive, &dir.join("copy")).unwrap();
+
+        assert_eq!(std::fs::read_to_string(dir.join("copy/a.txt")).unwrap(), "a");
+        assert_eq!(std::fs::read(dir.join("copy/nested/b.bin")).unwrap(), [0u8, 159, 146, 150]);
+        assert!(dir.join("copy/nested/empty").is_dir());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
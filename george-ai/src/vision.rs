@@ -0,0 +1,461 @@
+use crate::daemon::{DaemonError, DaemonSettings};
+use crate::logging::log;
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use bytes::Bytes;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// A vision model capable of interpreting screenshots for George.
+///
+/// Points are expressed as percentages (0-100) of the image width and height, which is the
+/// convention Molmo uses. The daemon converts them into screen pixels.
+#[async_trait]
+pub trait VisionBackend: Send + Sync {
+    /// Returns the point in the image which best matches the prompt.
+    async fn locate(&self, image: &Bytes, prompt: &str) -> Result<(f64, f64), DaemonError>;
+
+    /// Returns every point in the image which matches the prompt, or an empty list if nothing
+    /// does. The default asks [`VisionBackend::locate`] for a single point and treats an answer
+    /// without coordinates as no match.
+    async fn locate_all(&self, image: &Bytes, prompt: &str) -> Result<Vec<(f64, f64)>, DaemonError> {
+        match self.locate(image, prompt).await {
+            Ok(point) => Ok(vec![point]),
+            Err(DaemonError::FailedToParseCoordinates(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns all the text the model can read in the image.
+    async fn visible_text(&self, image: &Bytes, prompt: &str) -> Result<Vec<String>, DaemonError>;
+
+    /// Asks the model a free-form question about the image and returns its answer.
+    async fn ask(&self, image: &Bytes, prompt: &str) -> Result<String, DaemonError>;
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct FindResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct Message {
+    content: String,
+}
+
+/// The default vision backend which sends chat completion requests to Molmo served by an
+/// OpenAI compatible server such as vLLM.
+pub struct MolmoBackend {
+    client: Client,
+    settings: DaemonSettings,
+}
+
+impl MolmoBackend {
+    pub fn new(settings: DaemonSettings) -> Self {
+        Self {
+            client: Client::new(),
+            settings,
+        }
+    }
+
+    pub(crate) fn with_client(client: Client, settings: DaemonSettings) -> Self {
+        Self { client, settings }
+    }
+
+    fn request_body(&self, prompt: &str, image_url: &str) -> Value {
+        let settings = &self.settings;
+
+        if let Some(template) = &settings.request_template {
+            return self.render_template(template, prompt, image_url);
+        }
+
+        let mut request_body = json!({
+            "model": settings.model,
+            "messages": [
+                {
+                    "role": "user",
+                    "content": [
+                        {"type": "text", "text": prompt},
+                        {"type": "image_url", "image_url": {"url": image_url}}
+                    ]
+                }
+            ],
+            "temperature": settings.temperature,
+        });
+
+        if let Some(top_k) = settings.top_k {
+            request_body["top_k"] = json!(top_k);
+        }
+        if let Some(top_p) = settings.top_p {
+            request_body["top_p"] = json!(top_p);
+        }
+        if let Some(max_tokens) = settings.max_tokens {
+            request_body["max_tokens"] = json!(max_tokens);
+        }
+
+        request_body
+    }
+
+    fn render_template(&self, template: &Value, prompt: &str, image_url: &str) -> Value {
+        let settings = &self.settings;
+
+        match template {
+            Value::String(text) => match text.as_str() {
+                "{{temperature}}" => json!(settings.temperature),
+                "{{top_k}}" => json!(settings.top_k),
+                "{{top_p}}" => json!(settings.top_p),
+                "{{max_tokens}}" => json!(settings.max_tokens),
+                _ => Value::String(
+                    text.replace("{{model}}", &settings.model)
+                        .replace("{{prompt}}", prompt)
+                        .replace("{{image_url}}", image_url)
+                ),
+            },
+            Value::Array(values) => Value::Array(
+                values.iter().map(|value| self.render_template(value, prompt, image_url)).collect()
+            ),
+            Value::Object(map) => Value::Object(
+                map.iter().map(|(key, value)| (key.clone(), self.render_template(value, prompt, image_url))).collect()
+            ),
+            other => other.clone(),
+        }
+    }
+
+    pub(crate) fn parse_visible_text(&self, content: &str) -> Result<Vec<String>, DaemonError> {
+        let re = Regex::new(r#""(.*?)""#).unwrap(); // Matches text within double quotes
+        let mut visible_text = Vec::new();
+
+
+        for cap in re.captures_iter(content) {
+            if let Some(text) = cap.get(1) {
+                visible_text.push(text.as_str().trim_end_matches('\\').to_string());
+            }
+        }
+
+        if visible_text.is_empty() {
+            return Err(DaemonError::FailedToParseExistence(String::from(content)));
+        }
+
+        Ok(visible_text)
+    }
+
+    fn parse_coordinates(&self, content: &str) -> Result<(f64, f64), DaemonError> {
+        self.parse_all_coordinates(content).first().copied()
+            .ok_or_else(|| DaemonError::FailedToParseCoordinates(String::from(content)))
+    }
+
+    pub(crate) fn parse_all_coordinates(&self, content: &str) -> Vec<(f64, f64)> {
+        let re_xml = Regex::new(r#"x\d*="\s*([0-9]+(?:\.[0-9]+)?)"\s+y\d*="\s*([0-9]+(?:\.[0-9]+)?)"#).unwrap();
+        let re_parens = Regex::new(r#"\(?\s*(\d+(?:\.\d+)?)\s*,\s*(\d+(?:\.\d+)?)\s*\)?"#).unwrap();
+        let mut all_points = Vec::new();
+
+
+        for re in [&re_xml, &re_parens] {
+            for cap in re.captures_iter(content) {
+                if let (Some(x), Some(y)) = (cap.get(1), cap.get(2)) {
+                    if let (Ok(x), Ok(y)) = (x.as_str().parse::<f64>(), y.as_str().parse::<f64>()) {
+                        if x <= 100.0 && y <= 100.0 && !all_points.contains(&(x, y)) {
+                            all_points.push((x, y));
+                        }
+                    }
+                }
+            }
+        }
+
+        all_points
+    }
+}
+
+#[async_trait]
+impl VisionBackend for MolmoBackend {
+    async fn locate(&self, image: &Bytes, prompt: &str) -> Result<(f64, f64), DaemonError> {
+        let content = self.ask(image, prompt).await?;
+
+        self.parse_coordinates(content.trim())
+    }
+
+    async fn locate_all(&self, image: &Bytes, prompt: &str) -> Result<Vec<(f64, f64)>, DaemonError> {
+        let content = self.ask(image, prompt).await?;
+
+        Ok(self.parse_all_coordinates(content.trim()))
+    }
+
+    async fn visible_text(&self, image: &Bytes, prompt: &str) -> Result<Vec<String>, DaemonError> {
+        let content = self.ask(image, prompt).await?;
+
+        self.parse_visible_text(content.trim().to_lowercase().as_str())
+    }
+
+    async fn ask(&self, image: &Bytes, prompt: &str) -> Result<String, DaemonError> {
+        log!();
+        log!("prompt: {}", prompt);
+        let (image, mime_type) = self.settings.image_options.apply(image)?;
+        let image_base64 = general_purpose::STANDARD.encode(&image);
+        let request_body = self.request_body(prompt, &format!("data:{};base64,{}", mime_type, image_base64));
+
+        let mut request = self.client
+            .post(format!("{}/v1/chat/completions", self.settings.vision_llm_url))
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Bearer {}", self.settings.vision_llm_auth_token));
+
+        for (name, value) in &self.settings.headers {
+            request = request.header(name, value);
+        }
+        if let Some(request_timeout) = self.settings.request_timeout {
+            request = request.timeout(request_timeout);
+        }
+
+        let response = request
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_else(|_| String::from("Unable to retrieve response body"));
+            return Err(DaemonError::VisionRequestFailed { status, body });
+        }
+
+        let response_body: FindResponse = response.json().await?;
+        let response_text = serde_json::to_string(&response_body)?;
+        log!("Full response body: {}", response_text);
+
+        let content = response_body.choices.first()
+            .ok_or_else(|| DaemonError::Unexpected(format!("No choices in response. Prompt: {}", prompt)))?
+            .message.content.clone();
+
+        log!("content: {:?}", content);
+        Ok(content)
+    }
+}
+
+/// Wraps a vision backend so at most a fixed number of requests are in flight at once, queueing
+/// the rest. Clones share the same limit, so one limiter can protect a GPU server from every
+/// George in a [`crate::GeorgePool`].
+///
+/// # Example
+///
+/// ```rust
+/// use george_ai::{DaemonSettings, George, LimitedVisionBackend, MolmoBackend};
+///
+/// let molmo = MolmoBackend::new(DaemonSettings::new("https://your-molmo-llm.com"));
+/// let limiter = LimitedVisionBackend::new(molmo, 4);
+///
+/// let first = George::with_vision_backend(limiter.clone());
+/// let second = George::with_vision_backend(limiter);
+/// ```
+#[derive(Clone)]
+pub struct LimitedVisionBackend {
+    backend: Arc<dyn VisionBackend>,
+    permits: Arc<Semaphore>,
+}
+
+impl LimitedVisionBackend {
+    /// # Arguments
+    ///
+    /// * `backend` - The backend which answers the requests.
+    /// * `max_concurrent_requests` - How many requests may be in flight at once, at least 1.
+    pub fn new(backend: impl VisionBackend + 'static, max_concurrent_requests: usize) -> Self {
+        Self::with_permits(Arc::new(backend), Arc::new(Semaphore::new(max_concurrent_requests.max(1))))
+    }
+
+    pub(crate) fn with_permits(backend: Arc<dyn VisionBackend>, permits: Arc<Semaphore>) -> Self {
+        Self { backend, permits }
+    }
+
+    async fn acquire(&self) -> Result<tokio::sync::SemaphorePermit<'_>, DaemonError> {
+        self.permits.acquire().await
+            .map_err(|e| DaemonError::Unexpected(format!("Vision request limiter closed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl VisionBackend for LimitedVisionBackend {
+    async fn locate(&self, image: &Bytes, prompt: &str) -> Result<(f64, f64), DaemonError> {
+        let _permit = self.acquire().await?;
+        self.backend.locate(image, prompt).await
+    }
+
+    async fn locate_all(&self, image: &Bytes, prompt: &str) -> Result<Vec<(f64, f64)>, DaemonError> {
+        let _permit = self.acquire().await?;
+        self.backend.locate_all(image, prompt).await
+    }
+
+    async fn visible_text(&self, image: &Bytes, prompt: &str) -> Result<Vec<String>, DaemonError> {
+        let _permit = self.acquire().await?;
+        self.backend.visible_text(image, prompt).await
+    }
+
+    async fn ask(&self, image: &Bytes, prompt: &str) -> Result<String, DaemonError> {
+        let _permit = self.acquire().await?;
+        self.backend.ask(image, prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn backend() -> MolmoBackend {
+        MolmoBackend::new(DaemonSettings::new("https://doesnotmatter.com"))
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        let backend = backend();
+        let input = r#"<points x1="0.6" y1="13.0" x2="104.7" y2="12.8" alt="the point coordinate of the sign up name input field">the point coordinate of the sign up name input field</points>"#;
+        let result = backend.parse_coordinates(input).unwrap();
+        assert_eq!(result, (0.6, 13.0));
+    }
+
+    #[test]
+    fn test_parse_coordinates_in_parens() {
+        let backend = backend();
+        let input = r#"The center of the name input field is at coordinates (10.9, 14.1) in the image. This point represents the midpoint of the horizontal rectangle that contains the input field for the user's name.""#;
+        let result = backend.parse_coordinates(input).unwrap();
+        assert_eq!(result, (10.9, 14.1));
+    }
+
+    #[test]
+    fn test_parse_all_coordinates() {
+        let backend = backend();
+        let input = r#"<points x1="12.5" y1="30.1" x2="12.5" y2="45.0" x3="12.5" y3="60.2" alt="delete buttons">delete buttons</points>"#;
+        let result = backend.parse_all_coordinates(input);
+        assert_eq!(result, vec![(12.5, 30.1), (12.5, 45.0), (12.5, 60.2)]);
+        assert!(backend.parse_all_coordinates("There are no delete buttons in the image.").is_empty());
+    }
+
+    #[test]
+    fn test_parse_the_visible_text() {
+        let backend = backend();
+        let input = r#" Here's an array list containing all the text visible on the screen:\n\n[\n  \"host.docker.internal:3001\",\n  \"Firefox Privacy Notice\",\n  \"End-to-End Test\",\n  \"Name\",\n  \"Phone\",\n  \"Email\",\n  \"First Programmer\",\n  \"Analytical Engine\",\n  \"Programming\",\n  \"Submit\"\n]\n\nThis list includes all the text elements present in the browser tab, address bar, form fields, and buttons visible in the image."#;
+        let result = backend.parse_visible_text(input).unwrap();
+        assert_eq!(result, vec![
+            "host.docker.internal:3001",
+            "Firefox Privacy Notice",
+            "End-to-End Test",
+            "Name",
+            "Phone",
+            "Email",
+            "First Programmer",
+            "Analytical Engine",
+            "Programming",
+            "Submit"
+        ]);
+    }
+
+    #[test]
+    fn test_request_body_uses_settings() {
+        let settings = DaemonSettings::new("https://doesnotmatter.com")
+            .set_model(String::from("my-molmo"))
+            .set_temperature(0.5)
+            .set_top_k(None)
+            .set_max_tokens(Some(64));
+        let backend = MolmoBackend::new(settings);
+
+        let body = backend.request_body("find the button", "data:image/png;base64,AAAA");
+
+        assert_eq!(body["model"], "my-molmo");
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["max_tokens"], 64);
+        assert!(body.get("top_k").is_none());
+        assert_eq!(body["messages"][0]["content"][0]["text"], "find the button");
+    }
+
+    #[test]
+    fn test_request_body_from_template() {
+        let settings = DaemonSettings::new("https://doesnotmatter.com")
+            .set_model(String::from("qwen-vl"))
+            .set_request_template(json!({
+                "model": "{{model}}",
+                "prompt": "USER: {{prompt}}",
+                "images": ["{{image_url}}"],
+                "temperature": "{{temperature}}",
+                "top_p": "{{top_p}}",
+                "stream": false
+            }));
+        let backend = MolmoBackend::new(settings);
+
+        let body = backend.request_body("find the button", "data:image/png;base64,AAAA");
+
+        assert_eq!(body, json!({
+            "model": "qwen-vl",
+            "prompt": "USER: find the button",
+            "images": ["data:image/png;base64,AAAA"],
+            "temperature": 0.0,
+            "top_p": null,
+            "stream": false
+        }));
+    }
+
+    struct SlowBackend {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl VisionBackend for SlowBackend {
+        async fn locate(&self, _image: &Bytes, _prompt: &str) -> Result<(f64, f64), DaemonError> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            Ok((50.0, 50.0))
+        }
+
+        async fn visible_text(&self, _image: &Bytes, _prompt: &str) -> Result<Vec<String>, DaemonError> {
+            Ok(Vec::new())
+        }
+
+        async fn ask(&self, _image: &Bytes, _prompt: &str) -> Result<String, DaemonError> {
+            Ok(String::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_limited_backend_caps_concurrent_requests() {
+        let slow = Arc::new(SlowBackend { in_flight: 0.into(), max_in_flight: 0.into() });
+        let limiter = LimitedVisionBackend::with_permits(slow.clone(), Arc::new(Semaphore::new(2)));
+        let image = Bytes::new();
+
+        let requests = (0..6).map(|_| limiter.locate(&image, "button"));
+        futures_util::future::try_join_all(requests).await.unwrap();
+
+        assert_eq!(slow.max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    struct BlindBackend;
+
+    #[async_trait]
+    impl VisionBackend for BlindBackend {
+        async fn locate(&self, _image: &Bytes, _prompt: &str) -> Result<(f64, f64), DaemonError> {
+            Err(DaemonError::FailedToParseCoordinates(String::from("There are none.")))
+        }
+
+        async fn visible_text(&self, _image: &Bytes, _prompt: &str) -> Result<Vec<String>, DaemonError> {
+            Ok(Vec::new())
+        }
+
+        async fn ask(&self, _image: &Bytes, _prompt: &str) -> Result<String, DaemonError> {
+            Ok(String::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_locate_all_without_a_match() {
+        let points = BlindBackend.locate_all(&Bytes::new(), "button").await.unwrap();
+
+        assert!(points.is_empty());
+    }
+}
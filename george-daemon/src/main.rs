@@ -4,6 +4,10 @@ use crate::{
     routes::click_route::click_handler,
     routes::screenshot_route::screenshot_handler,
     routes::type_route::type_handler,
+    routes::key_route::key_handler,
+    routes::scroll_route::scroll_handler,
+    routes::mouse_route::{down_handler, drag_handler, move_handler, position_handler, up_handler},
+    routes::display_route::display_handler,
     routes::root_route::root_handler,
 };
 use axum::routing::post;
@@ -17,6 +21,14 @@ async fn main() {
         .route("/screenshot", get(screenshot_handler))
         .route("/click", post(click_handler))
         .route("/type", post(type_handler))
+        .route("/key", post(key_handler))
+        .route("/scroll", post(scroll_handler))
+        .route("/mouse", get(position_handler))
+        .route("/mouse/move", post(move_handler))
+        .route("/mouse/down", post(down_handler))
+        .route("/mouse/up", post(up_handler))
+        .route("/drag", post(drag_handler))
+        .route("/display", get(display_handler))
         .route("/healthz", get(healthz));
 
 
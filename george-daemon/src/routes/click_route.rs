@@ -1,13 +1,62 @@
 use axum::Json;
 use axum::response::IntoResponse;
-use enigo::{Button, Coordinate, Direction, Enigo, Mouse, Settings};
-use serde::Deserialize;
+use enigo::{Button, Coordinate, Direction, Enigo, Key, Keyboard, Mouse, Settings};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Middle,
+    Right,
+}
+
+impl MouseButton {
+    pub fn button(&self) -> Button {
+        match self {
+            MouseButton::Left => Button::Left,
+            MouseButton::Middle => Button::Middle,
+            MouseButton::Right => Button::Right,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Modifier {
+    Control,
+    Shift,
+    Alt,
+    Meta,
+}
+
+impl Modifier {
+    pub fn key(&self) -> Key {
+        match self {
+            Modifier::Control => Key::Control,
+            Modifier::Shift => Key::Shift,
+            Modifier::Alt => Key::Alt,
+            Modifier::Meta => Key::Meta,
+        }
+    }
+}
+
+fn default_count() -> u32 {
+    1
+}
+
 #[derive(Deserialize)]
 pub struct ClickPayload {
     x: i32,
     y: i32,
+    #[serde(default)]
+    button: MouseButton,
+    #[serde(default = "default_count")]
+    count: u32,
+    #[serde(default)]
+    modifiers: Vec<Modifier>,
 }
 
 
@@ -15,11 +64,23 @@ pub async fn click_handler(Json(payload): Json<ClickPayload>) -> impl IntoRespon
     let mut enigo = Enigo::new(&Settings::default()).unwrap();
 
     enigo.move_mouse(payload.x, payload.y, Coordinate::Abs).unwrap();
-    enigo.button(Button::Left, Direction::Click).unwrap();
+
+    for modifier in &payload.modifiers {
+        enigo.key(modifier.key(), Direction::Press).unwrap();
+    }
+    for _ in 0..payload.count {
+        enigo.button(payload.button.button(), Direction::Click).unwrap();
+    }
+    for modifier in payload.modifiers.iter().rev() {
+        enigo.key(modifier.key(), Direction::Release).unwrap();
+    }
 
     Json(json!({
         "status": "clicked",
         "x": payload.x,
-        "y": payload.y
+        "y": payload.y,
+        "button": payload.button,
+        "count": payload.count,
+        "modifiers": payload.modifiers
     }))
 }
@@ -0,0 +1,23 @@
+use axum::http::StatusCode;
+use axum::Json;
+use axum::response::IntoResponse;
+use screenshots::Screen;
+use serde_json::json;
+
+/// Returns the size of the primary screen, or `None` when no screen is available.
+pub fn display_size() -> Option<(u32, u32)> {
+    let screens = Screen::all().ok()?;
+    let screen = screens.first()?;
+
+    Some((screen.display_info.width, screen.display_info.height))
+}
+
+pub async fn display_handler() -> impl IntoResponse {
+    match display_size() {
+        Some((width, height)) => Ok(Json(json!({
+            "width": width,
+            "height": height
+        }))),
+        None => Err((StatusCode::INTERNAL_SERVER_ERROR, String::from("Failed to get screens"))),
+    }
+}
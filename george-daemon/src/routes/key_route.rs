@@ -0,0 +1,134 @@
+use axum::http::StatusCode;
+use axum::Json;
+use axum::response::IntoResponse;
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyDirection {
+    /// Presses every key in the chord, then releases them in reverse order.
+    #[default]
+    Click,
+    /// Presses every key in the chord without releasing them.
+    Down,
+    /// Releases every key in the chord in reverse order.
+    Up,
+}
+
+#[derive(Deserialize)]
+pub struct KeyPayload {
+    keys: String,
+    #[serde(default)]
+    direction: KeyDirection,
+}
+
+/// Parses a single key name such as `enter`, `ctrl`, `f5` or `a`.
+pub fn parse_key(name: &str) -> Option<Key> {
+    let key = match name.to_lowercase().as_str() {
+        "ctrl" | "control" => Key::Control,
+        "shift" => Key::Shift,
+        "alt" => Key::Alt,
+        "meta" | "super" | "win" | "cmd" => Key::Meta,
+        "enter" | "return" => Key::Return,
+        "tab" => Key::Tab,
+        "esc" | "escape" => Key::Escape,
+        "space" => Key::Space,
+        "backspace" => Key::Backspace,
+        "delete" | "del" => Key::Delete,
+        "up" | "arrowup" => Key::UpArrow,
+        "down" | "arrowdown" => Key::DownArrow,
+        "left" | "arrowleft" => Key::LeftArrow,
+        "right" | "arrowright" => Key::RightArrow,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "capslock" => Key::CapsLock,
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Unicode(c),
+                _ => return None,
+            }
+        }
+    };
+
+    Some(key)
+}
+
+/// Parses a chord of key names joined by `+`, e.g. `ctrl+shift+t`. A literal plus is written as
+/// `+` on its own or at the end of a chord, e.g. `ctrl++`.
+pub fn parse_chord(chord: &str) -> Result<Vec<Key>, String> {
+    let (held, last) = if chord == "+" {
+        ("", "+")
+    } else if let Some(held) = chord.strip_suffix("++") {
+        (held, "+")
+    } else {
+        chord.rsplit_once('+').unwrap_or(("", chord))
+    };
+
+    held.split('+')
+        .filter(|name| !held.is_empty() || !name.is_empty())
+        .chain(std::iter::once(last))
+        .map(|name| parse_key(name.trim()).ok_or_else(|| format!("Unknown key '{}' in '{}'", name, chord)))
+        .collect()
+}
+
+pub async fn key_handler(Json(payload): Json<KeyPayload>) -> impl IntoResponse {
+    let keys = match parse_chord(&payload.keys) {
+        Ok(keys) => keys,
+        Err(e) => return Err((StatusCode::BAD_REQUEST, e)),
+    };
+    let mut enigo = Enigo::new(&Settings::default()).unwrap();
+
+    if let KeyDirection::Click | KeyDirection::Down = payload.direction {
+        for key in &keys {
+            enigo.key(*key, Direction::Press).unwrap();
+        }
+    }
+    if let KeyDirection::Click | KeyDirection::Up = payload.direction {
+        for key in keys.iter().rev() {
+            enigo.key(*key, Direction::Release).unwrap();
+        }
+    }
+
+    Ok(Json(json!({
+        "status": "pressed",
+        "keys": payload.keys,
+        "direction": payload.direction
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chord() {
+        assert_eq!(parse_chord("ctrl+a"), Ok(vec![Key::Control, Key::Unicode('a')]));
+        assert_eq!(parse_chord("Ctrl+Shift+T"), Ok(vec![Key::Control, Key::Shift, Key::Unicode('T')]));
+        assert_eq!(parse_chord("enter"), Ok(vec![Key::Return]));
+        assert_eq!(parse_chord("ctrl++"), Ok(vec![Key::Control, Key::Unicode('+')]));
+        assert_eq!(parse_chord("+"), Ok(vec![Key::Unicode('+')]));
+    }
+
+    #[test]
+    fn test_parse_chord_with_unknown_key() {
+        assert!(parse_chord("ctrl+banana").is_err());
+        assert!(parse_chord("ctrl+").is_err());
+    }
+}
@@ -2,4 +2,8 @@ pub mod screenshot_route;
 pub mod click_route;
 pub mod type_route;
 pub mod root_route;
-pub mod health_route;
\ No newline at end of file
+pub mod health_route;
+pub mod key_route;
+pub mod scroll_route;
+pub mod mouse_route;
+pub mod display_route;
@@ -0,0 +1,145 @@
+use crate::routes::click_route::MouseButton;
+use axum::Json;
+use axum::response::IntoResponse;
+use enigo::{Coordinate, Direction, Enigo, Mouse, Settings};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize)]
+pub struct MovePayload {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize)]
+pub struct ButtonPayload {
+    #[serde(default)]
+    button: MouseButton,
+}
+
+fn default_steps() -> u32 {
+    10
+}
+
+fn default_step_delay_ms() -> u64 {
+    10
+}
+
+/// Presses the button at `from`, moves to `to` in `steps` evenly spaced moves and releases it.
+/// The intermediate moves make sure JavaScript drag libraries see mousemove events.
+#[derive(Deserialize)]
+pub struct DragPayload {
+    from: Point,
+    to: Point,
+    #[serde(default)]
+    button: MouseButton,
+    #[serde(default = "default_steps")]
+    steps: u32,
+    #[serde(default = "default_step_delay_ms")]
+    step_delay_ms: u64,
+}
+
+pub async fn move_handler(Json(payload): Json<MovePayload>) -> impl IntoResponse {
+    let mut enigo = Enigo::new(&Settings::default()).unwrap();
+
+    enigo.move_mouse(payload.x, payload.y, Coordinate::Abs).unwrap();
+
+    Json(json!({
+        "status": "moved",
+        "x": payload.x,
+        "y": payload.y
+    }))
+}
+
+pub async fn down_handler(Json(payload): Json<ButtonPayload>) -> impl IntoResponse {
+    let mut enigo = Enigo::new(&Settings::default()).unwrap();
+
+    enigo.button(payload.button.button(), Direction::Press).unwrap();
+
+    Json(json!({
+        "status": "pressed",
+        "button": payload.button
+    }))
+}
+
+pub async fn up_handler(Json(payload): Json<ButtonPayload>) -> impl IntoResponse {
+    let mut enigo = Enigo::new(&Settings::default()).unwrap();
+
+    enigo.button(payload.button.button(), Direction::Release).unwrap();
+
+    Json(json!({
+        "status": "released",
+        "button": payload.button
+    }))
+}
+
+pub async fn position_handler() -> impl IntoResponse {
+    let enigo = Enigo::new(&Settings::default()).unwrap();
+
+    let (x, y) = enigo.location().unwrap();
+
+    Json(json!({
+        "x": x,
+        "y": y
+    }))
+}
+
+/// Returns the points between `from` and `to`, excluding `from` and ending at `to`.
+pub fn drag_path(from: Point, to: Point, steps: u32) -> Vec<Point> {
+    let steps = steps.max(1);
+
+    (1..=steps).map(|step| {
+        let progress = step as f64 / steps as f64;
+        Point {
+            x: from.x + ((to.x - from.x) as f64 * progress).round() as i32,
+            y: from.y + ((to.y - from.y) as f64 * progress).round() as i32,
+        }
+    }).collect()
+}
+
+pub async fn drag_handler(Json(payload): Json<DragPayload>) -> impl IntoResponse {
+    let DragPayload { from, to, button, steps, step_delay_ms } = payload;
+
+    tokio::task::spawn_blocking(move || {
+        let mut enigo = Enigo::new(&Settings::default()).unwrap();
+        let step_delay = Duration::from_millis(step_delay_ms);
+
+        enigo.move_mouse(from.x, from.y, Coordinate::Abs).unwrap();
+        enigo.button(button.button(), Direction::Press).unwrap();
+        for point in drag_path(from, to, steps) {
+            sleep(step_delay);
+            enigo.move_mouse(point.x, point.y, Coordinate::Abs).unwrap();
+        }
+        sleep(step_delay);
+        enigo.button(button.button(), Direction::Release).unwrap();
+    }).await.unwrap();
+
+    Json(json!({
+        "status": "dragged",
+        "from": from,
+        "to": to,
+        "button": button,
+        "steps": steps
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_path() {
+        let path = drag_path(Point { x: 0, y: 0 }, Point { x: 100, y: -50 }, 4);
+        let path: Vec<(i32, i32)> = path.iter().map(|point| (point.x, point.y)).collect();
+
+        assert_eq!(path, vec![(25, -13), (50, -25), (75, -38), (100, -50)]);
+    }
+}
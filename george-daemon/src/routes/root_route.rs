@@ -1,7 +1,10 @@
+use crate::routes::display_route::display_size;
 use axum::response::Html;
 
-pub async fn root_handler() -> Html<&'static str> {
-    Html(
+pub async fn root_handler() -> Html<String> {
+    let (width, height) = display_size().unwrap_or((1024, 768));
+
+    Html(format!(
         r#"
         <!DOCTYPE html>
         <html lang="en">
@@ -10,23 +13,24 @@ pub async fn root_handler() -> Html<&'static str> {
             <meta name="viewport" content="width=device-width, initial-scale=1.0">
             <title>George Client Screenshot</title>
             <style>
-                body {
+                body {{
                     background-color: #f5f5f5;
-                }
+                }}
             </style>
             <script>
-                function refreshImage() {
+                function refreshImage() {{
                     const img = document.getElementById('screenshot');
                     img.src = '/screenshot?' + new Date().getTime();
-                }
+                }}
 
                 setInterval(refreshImage, 250);
             </script>
         </head>
         <body>
-            <img id="screenshot" src="/screenshot" alt="Screenshot" width="1024" height="768">
+            <img id="screenshot" src="/screenshot" alt="Screenshot" width="{}" height="{}">
         </body>
         </html>
-        "#
-    )
+        "#,
+        width, height
+    ))
 }
\ No newline at end of file
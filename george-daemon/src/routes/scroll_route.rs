@@ -0,0 +1,50 @@
+use axum::Json;
+use axum::response::IntoResponse;
+use enigo::{Axis, Coordinate, Enigo, Mouse, Settings};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollAxis {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+impl ScrollAxis {
+    pub fn axis(&self) -> Axis {
+        match self {
+            ScrollAxis::Vertical => Axis::Vertical,
+            ScrollAxis::Horizontal => Axis::Horizontal,
+        }
+    }
+}
+
+/// Scrolls by `amount` wheel clicks. Positive amounts scroll down or right. When `x` and `y` are
+/// given, the mouse is moved there first so the element under it receives the scroll.
+#[derive(Deserialize)]
+pub struct ScrollPayload {
+    amount: i32,
+    #[serde(default)]
+    axis: ScrollAxis,
+    x: Option<i32>,
+    y: Option<i32>,
+}
+
+pub async fn scroll_handler(Json(payload): Json<ScrollPayload>) -> impl IntoResponse {
+    let mut enigo = Enigo::new(&Settings::default()).unwrap();
+
+    if let (Some(x), Some(y)) = (payload.x, payload.y) {
+        enigo.move_mouse(x, y, Coordinate::Abs).unwrap();
+    }
+    enigo.scroll(payload.amount, payload.axis.axis()).unwrap();
+
+    Json(json!({
+        "status": "scrolled",
+        "amount": payload.amount,
+        "axis": payload.axis,
+        "x": payload.x,
+        "y": payload.y
+    }))
+}